@@ -33,5 +33,7 @@ async fn main() {
         .build("quote");
     let mut actor = MyActor::new().await;
     let mut app = dyer::App::<Entities>::new();
+    // exit gracefully on Ctrl+c
+    app.signals(true);
     app.run(&mut actor, &middleware, &pipeline).await.unwrap();
 }
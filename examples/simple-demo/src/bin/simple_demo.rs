@@ -33,5 +33,7 @@ async fn main() {
         (inner_t, exts_t)
     };
     app.exts_t(Box::new(f));
+    // exit gracefully on Ctrl+c
+    app.signals(true);
     app.run(&mut actor, &middleware, &pipeline).await.unwrap();
 }
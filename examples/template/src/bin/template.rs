@@ -26,5 +26,7 @@ async fn main() {
         .build("MyActor");
     let mut actor = MyActor::new().await;
     let mut app = dyer::App::<Entities>::new();
+    // exit gracefully on Ctrl+c
+    app.signals(true);
     app.run(&mut actor, &middleware, &pipeline).await.unwrap();
 }
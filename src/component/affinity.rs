@@ -0,0 +1,121 @@
+//! The [Affix] that a [Task] can be coupled with
+//!
+//! Affixes are often specific to a site, eg. cookies of a domain or a token of an API, a `Task`
+//! declares the ones it needs by marker, by domain or by a predicate, and is held back in the
+//! queue until a compatible `Affix` is available. The `Task` without affinity is coupled with
+//! any `Affix`.
+//!
+//! ```rust
+//! # use dyer::affinity::Affinity;
+//! # use dyer::{Affix, Body, Task};
+//! let task = Task::default();
+//! let affix = Affix::builder()
+//!     .domain("example.com")
+//!     .body(Body::empty())
+//!     .unwrap();
+//! assert!(Affinity::domain("api.example.com").accepts(&task, &affix));
+//! assert!(!Affinity::domain("example.org").accepts(&task, &affix));
+//! ```
+//!
+//! Note that the predicate is stored by its name like the parser, it has to be registered in
+//! [FNMAP] to be stored and loaded in session.
+//!
+//! [Affix]: crate::component::Affix
+//! [Task]: crate::component::Task
+//! [FNMAP]: crate::plugin::deser::FNMAP
+use crate::component::{Affix, Task};
+use crate::plugin::deser::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// the `Affix` that a `Task` can be coupled with
+#[derive(Deserialize, Serialize, Clone, PartialEq)]
+pub enum Affinity {
+    /// the `Affix` of the marker
+    Marker(String),
+    /// the `Affix` of the domain or of its parent, eg. an `Affix` of `example.com` serves
+    /// `api.example.com`
+    Domain(String),
+    /// the `Affix` accepted by the predicate `fn(&Task, &Affix) -> bool`
+    Predicate(
+        #[serde(serialize_with = "serde_fn::serfn")]
+        #[serde(deserialize_with = "serde_fn::defn")]
+        *const (),
+    ),
+}
+
+impl Affinity {
+    /// the `Affix` of `marker`
+    pub fn marker<T: Into<String>>(marker: T) -> Self {
+        Affinity::Marker(marker.into())
+    }
+
+    /// the `Affix` of `domain` or of its parent
+    pub fn domain<T: Into<String>>(domain: T) -> Self {
+        Affinity::Domain(domain.into().to_lowercase())
+    }
+
+    /// the `Affix` accepted by `f`
+    pub fn predicate(f: fn(&Task, &Affix) -> bool) -> Self {
+        Affinity::Predicate(f as *const ())
+    }
+
+    /// `task` can be coupled with `affix` or not
+    pub fn accepts(&self, task: &Task, affix: &Affix) -> bool {
+        match self {
+            Affinity::Marker(marker) => affix.metap.info.marker == *marker,
+            Affinity::Domain(domain) => match affix.domain() {
+                Some(scope) => {
+                    domain == scope
+                        || (domain.ends_with(scope)
+                            && domain[..domain.len() - scope.len()].ends_with('.'))
+                }
+                None => false,
+            },
+            Affinity::Predicate(ptr) => {
+                let f =
+                    unsafe { std::mem::transmute::<*const (), fn(&Task, &Affix) -> bool>(*ptr) };
+                f(task, affix)
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Affinity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Affinity::Marker(marker) => f.debug_tuple("Marker").field(marker).finish(),
+            Affinity::Domain(domain) => f.debug_tuple("Domain").field(domain).finish(),
+            Affinity::Predicate(ptr) => {
+                let name = serde_fn::query(None, Some(*ptr)).map_or("Unknow", |(n, _)| n);
+                f.debug_tuple("Predicate").field(&name).finish()
+            }
+        }
+    }
+}
+
+#[test]
+fn test_affinity() {
+    use crate::component::Body;
+
+    fn with_token(_: &Task, affix: &Affix) -> bool {
+        affix.headers().contains_key("authorization")
+    }
+    let task = Task::default();
+    let mut affix = Affix::builder()
+        .domain("Example.com")
+        .header("authorization", "Bearer token")
+        .body(Body::empty())
+        .unwrap();
+    affix.metap.info.marker = "api".into();
+    assert!(Affinity::marker("api").accepts(&task, &affix));
+    assert!(!Affinity::marker("web").accepts(&task, &affix));
+    assert!(Affinity::domain("example.com").accepts(&task, &affix));
+    assert!(Affinity::domain("API.example.com").accepts(&task, &affix));
+    assert!(!Affinity::domain("badexample.com").accepts(&task, &affix));
+    assert!(!Affinity::domain("com").accepts(&task, &affix));
+    assert!(Affinity::predicate(with_token).accepts(&task, &affix));
+    let plain = Affix::builder().body(Body::empty()).unwrap();
+    assert!(!Affinity::domain("example.com").accepts(&task, &plain));
+    assert!(!Affinity::predicate(with_token).accepts(&task, &plain));
+}
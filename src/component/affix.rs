@@ -15,6 +15,7 @@ use std::hash::{Hash, Hasher};
 
 use crate::component::{body::Body, info::Info};
 use crate::plugin::deser::*;
+use crate::utils;
 use http::{header::HeaderName, Extensions, HeaderMap, HeaderValue};
 use serde::{Deserialize, Serialize};
 use std::fmt;
@@ -40,6 +41,11 @@ pub struct Affix {
 pub struct MetaAffix {
     /// info about the Affix
     pub info: Info,
+    /// the domain it serves, together with its subdomains, see [Affinity]
+    ///
+    /// [Affinity]: crate::component::affinity::Affinity
+    #[serde(default)]
+    pub domain: Option<String>,
     /// additional arguments for extensive application
     #[serde(skip)]
     pub exts: Extensions,
@@ -70,6 +76,7 @@ impl fmt::Debug for MetaAffix {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("MetaAffix")
             .field("info", &self.info)
+            .field("domain", &self.domain)
             .finish()
     }
 }
@@ -150,6 +157,21 @@ impl Affix {
         &mut self.metap.exts
     }
 
+    /// get the domain that `Affix` serves
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use dyer::affix::*;
+    /// let affix = Affix::builder()
+    ///     .domain("example.com")
+    ///     .body(());
+    /// assert_eq!(affix.domain(), Some("example.com"));
+    /// ```
+    pub fn domain(&self) -> Option<&str> {
+        self.metap.domain.as_deref()
+    }
+
     /// get the rank of `Affix`
     ///
     /// # Examples
@@ -627,6 +649,54 @@ impl AffixBuilder {
         self
     }
 
+    /// set the domain that `Affix` serves, together with its subdomains
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use dyer::affix::*;
+    /// let affix = AffixBuilder::new()
+    ///     .domain("example.com")
+    ///     .body(());
+    /// assert_eq!(affix.meta_ref().domain, Some("example.com".into()));
+    /// ```
+    pub fn domain<T: Into<String>>(mut self, domain: T) -> Self {
+        self.meta.domain = Some(domain.into().to_lowercase());
+        self
+    }
+
+    /// make the `Affix` available `secs` seconds later from now
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use dyer::affix::*;
+    /// let affix = AffixBuilder::new()
+    ///     .delay(60.0)
+    ///     .body(());
+    /// assert!(affix.info_ref().able > dyer::utils::now());
+    /// ```
+    pub fn delay(mut self, secs: f64) -> Self {
+        self.meta.info.able = utils::now() + secs.max(0.0);
+        self
+    }
+
+    /// make the `Affix` available no earlier than the time stamp `stamp` in seconds
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use dyer::affix::*;
+    /// let affix = AffixBuilder::new()
+    ///     .not_before(1893456000.0)
+    ///     .body(());
+    /// assert_eq!(affix.info_ref().able, 1893456000.0);
+    /// ```
+    pub fn not_before(mut self, stamp: f64) -> Self {
+        self.meta.info.able = stamp;
+        self
+    }
+
     /// Take this `AffixBuilder` and combine the body to create a `Affix`
     ///
     /// # Examples
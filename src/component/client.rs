@@ -1,25 +1,225 @@
 //! the [Client] that asynchronously executes [Request],
 //!
 //! Note that polling the [Request] requires tokio runtime.
+//!
+//! The timeouts and `max_body` of [Request] are enforced here, a failed [Request] is returned
+//! as [MetaResponse] whose `failure` tells the reason.
 
 use crate::component::Body;
 use crate::component::{utils, Request, Response};
 use crate::request::Exts;
-use crate::response::InnerResponse;
-use crate::response::MetaResponse;
+use crate::response::{FailureKind, InnerResponse, MetaResponse, TimeoutKind};
 use futures_util::{future::join_all, Future};
-use http::Extensions;
-use hyper::body::Buf;
+use http::{Extensions, Uri};
+use hyper::body::HttpBody;
+use hyper::client::connect::dns::{GaiAddrs, GaiResolver, Name};
 use hyper::client::HttpConnector;
+use hyper::service::Service;
 #[cfg(feature = "proxy")]
 use hyper_proxy::ProxyConnector;
-use hyper_tls::HttpsConnector;
+use hyper_tls::{native_tls, HttpsConnector};
 use std::collections::HashMap;
-use std::io::{BufReader, Read};
+use std::io::Read;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
 
-type ClientPlain = hyper::Client<HttpsConnector<HttpConnector>>;
+type ClientPlain = hyper::Client<TimedConnector<HttpsConnector<HttpConnector<Resolver>>>>;
 #[cfg(feature = "proxy")]
-type ClientProxy = hyper::Client<ProxyConnector<HttpConnector>>;
+type ClientProxy = hyper::Client<TimedConnector<ProxyConnector<HttpConnector<Resolver>>>>;
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+tokio::task_local! {
+    /// the connect timeout of the `Request` being executed
+    static CONNECT_TIMEOUT: Option<Duration>;
+}
+
+/// the error that the connection is not established within the connect timeout
+#[derive(Debug)]
+struct ConnectTimeout;
+
+impl std::fmt::Display for ConnectTimeout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "connect timeout")
+    }
+}
+
+impl std::error::Error for ConnectTimeout {}
+
+/// the error that the host name of the `Request` is not resolved
+#[derive(Debug)]
+pub struct ResolveError(std::io::Error);
+
+impl std::fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "failed to resolve: {}", self.0)
+    }
+}
+
+impl std::error::Error for ResolveError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+/// A resolver that resolves the host name as [GaiResolver] does, and fails with
+/// [ResolveError], by which the failure is told from others
+#[derive(Clone, Debug)]
+pub struct Resolver {
+    inner: GaiResolver,
+}
+
+impl Resolver {
+    /// create an instance of [Resolver]
+    pub fn new() -> Self {
+        Self {
+            inner: GaiResolver::new(),
+        }
+    }
+}
+
+impl Default for Resolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Service<Name> for Resolver {
+    type Response = GaiAddrs;
+    type Error = ResolveError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(ResolveError)
+    }
+
+    fn call(&mut self, name: Name) -> Self::Future {
+        let fut = self.inner.call(name);
+        Box::pin(async move { fut.await.map_err(ResolveError) })
+    }
+}
+
+/// A connector that gives up establishing the connection once the connect timeout of
+/// the `Request` being executed is exceeded
+#[derive(Clone)]
+pub struct TimedConnector<C> {
+    inner: C,
+}
+
+impl<C> TimedConnector<C> {
+    /// wrap the connector `inner`
+    pub fn new(inner: C) -> Self {
+        Self { inner }
+    }
+}
+
+impl<C> Service<Uri> for TimedConnector<C>
+where
+    C: Service<Uri>,
+    C::Response: Send + 'static,
+    C::Error: Into<BoxError>,
+    C::Future: Send + 'static,
+{
+    type Response = C::Response;
+    type Error = BoxError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, dst: Uri) -> Self::Future {
+        let timeout = CONNECT_TIMEOUT.try_with(|timeout| *timeout).ok().flatten();
+        let fut = self.inner.call(dst);
+        Box::pin(async move {
+            match timeout {
+                Some(timeout) => match tokio::time::timeout(timeout, fut).await {
+                    Ok(result) => result.map_err(Into::into),
+                    Err(_) => Err(ConnectTimeout.into()),
+                },
+                None => fut.await.map_err(Into::into),
+            }
+        })
+    }
+}
+
+/// await `fut` for no more than `limit` seconds, `None` if exceeded
+async fn within<F: Future>(limit: Option<f64>, fut: F) -> Option<F::Output> {
+    match limit {
+        Some(secs) => tokio::time::timeout(Duration::from_secs_f64(secs.max(0.0)), fut)
+            .await
+            .ok(),
+        None => Some(fut.await),
+    }
+}
+
+/// the kind of failure that causes the error
+fn failure_kind(e: &hyper::Error) -> FailureKind {
+    let mut source = std::error::Error::source(e);
+    while let Some(err) = source {
+        if err.is::<ConnectTimeout>() {
+            return FailureKind::Timeout(TimeoutKind::Connect);
+        }
+        if err.is::<native_tls::Error>() {
+            return FailureKind::Tls;
+        }
+        if let Some(io) = err.downcast_ref::<std::io::Error>() {
+            if io.kind() == std::io::ErrorKind::ConnectionRefused {
+                return FailureKind::ConnectRefused;
+            }
+            // the source of `io::Error` skips the error it wraps
+            if let Some(inner) = io.get_ref() {
+                if inner.is::<native_tls::Error>() {
+                    return FailureKind::Tls;
+                }
+            }
+        }
+        if err.is::<ResolveError>() {
+            return FailureKind::Dns;
+        }
+        source = err.source();
+    }
+    FailureKind::Other
+}
+
+/// read the whole body, no more than `max` bytes
+async fn read_body(mut body: hyper::Body, max: Option<usize>) -> Result<Vec<u8>, FailureKind> {
+    let mut data = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(|_| FailureKind::BodyRead)?;
+        if matches!(max, Some(max) if data.len() + chunk.len() > max) {
+            return Err(FailureKind::TooLarge);
+        }
+        data.extend_from_slice(&chunk);
+    }
+    Ok(data)
+}
+
+/// decompress the body according to `content-encoding`, no more than `max` bytes
+fn decode(
+    data: Vec<u8>,
+    encoding: Option<&str>,
+    max: Option<usize>,
+) -> Result<Vec<u8>, FailureKind> {
+    let reader: Box<dyn Read + '_> = match encoding {
+        #[cfg(feature = "compression")]
+        Some("gzip") | Some("deflate") => Box::new(flate2::read::GzDecoder::new(&data[..])),
+        #[cfg(feature = "compression")]
+        Some("br") => Box::new(brotli2::read::BrotliDecoder::new(&data[..])),
+        _ => Box::new(&data[..]),
+    };
+    // one more byte to tell whether it exceeds
+    let limit = max.map(|max| max as u64 + 1).unwrap_or(u64::MAX);
+    let mut decoded = Vec::new();
+    reader
+        .take(limit)
+        .read_to_end(&mut decoded)
+        .map_err(|_| FailureKind::Decode)?;
+    if matches!(max, Some(max) if decoded.len() > max) {
+        return Err(FailureKind::TooLarge);
+    }
+    Ok(decoded)
+}
 
 /// represent an client that invoke make requests
 pub enum ClientType {
@@ -56,7 +256,9 @@ impl Client {
                 return d;
             }
         }
-        let https = HttpsConnector::new();
+        let mut http = HttpConnector::new_with_resolver(Resolver::new());
+        http.enforce_http(false);
+        let https = TimedConnector::new(HttpsConnector::new_with_connector(http));
         let client = hyper::Client::builder().build::<_, hyper::Body>(https);
         let downloader = Client {
             id,
@@ -156,67 +358,77 @@ impl Client {
     /// this function requires a `Request` and `hyper::Client` to return the Response
     /// Poll the `Request`, and asynchronously aggregate data from
     /// server.
+    ///
+    /// the `MetaResponse` is returned with its `failure` set if failed
     pub async fn request(&self, req: Request) -> Result<Response, MetaResponse> {
         let (mta, req, ext_t, ext_p) = req.into();
         let mut mta = MetaResponse::from(mta);
+        let timeout = mta.info.timeout;
         let tic = utils::now();
-        let result = match self.inner {
-            ClientType::Plain(ref client) => client.request(req).await,
-            #[cfg(feature = "proxy")]
-            ClientType::Proxy(ref client) => client.request(req).await,
+        let connect = timeout
+            .connect
+            .map(|secs| Duration::from_secs_f64(secs.max(0.0)));
+        let head = CONNECT_TIMEOUT.scope(connect, async {
+            match self.inner {
+                ClientType::Plain(ref client) => client.request(req).await,
+                #[cfg(feature = "proxy")]
+                ClientType::Proxy(ref client) => client.request(req).await,
+            }
+        });
+        // the head is bounded by the total timeout as well
+        let (limit, kind) = match (timeout.first_byte, timeout.total) {
+            (Some(first_byte), Some(total)) if total < first_byte => {
+                (Some(total), TimeoutKind::Total)
+            }
+            (Some(first_byte), _) => (Some(first_byte), TimeoutKind::FirstByte),
+            (None, total) => (total, TimeoutKind::Total),
+        };
+        let result = match within(limit, head).await {
+            Some(result) => result,
+            None => {
+                log::error!("Timeout request: {:?} {:?}", mta.info.from, kind);
+                mta.failure = Some(FailureKind::Timeout(kind));
+                return Err(mta);
+            }
         };
         let toc = utils::now();
         match result {
             Ok(response) => {
                 let (parts, body_future) = response.into_parts();
-                let bod = hyper::body::aggregate(body_future).await;
-                match bod {
-                    Ok(body) => {
-                        //let mut data = Bytes::from(body.bytes());
-                        let mut reader = BufReader::new(body.reader());
-                        // Response Content
-                        let mut data = Vec::new();
-                        if let Some(t) = parts.headers.get("content-encoding") {
-                            match t.to_str() {
-                                #[cfg(feature = "compression")]
-                                Ok("gzip") | Ok("deflate") => {
-                                    let mut gz = flate2::read::GzDecoder::new(reader);
-                                    gz.read_to_end(&mut data).unwrap();
-                                }
-                                #[cfg(feature = "compression")]
-                                Ok("br") => {
-                                    let mut br = brotli2::read::BrotliDecoder::new(reader);
-                                    br.read_to_end(&mut data).unwrap();
-                                }
-                                _ => {
-                                    reader.read_to_end(&mut data).unwrap();
-                                }
-                            }
-                        } else {
-                            reader.read_to_end(&mut data).unwrap();
-                        }
-
-                        let body = Body::from(data);
-                        let inn = InnerResponse {
-                            status: parts.status,
-                            version: parts.version,
-                            headers: parts.headers,
-                            extensions: Exts(ext_t, ext_p, Extensions::new(), parts.extensions),
-                        };
-                        mta.info.gap = toc - tic;
-                        let ret = Response::from_parts(inn, body, mta);
-                        Ok(ret)
+                let max_body = mta.info.max_body;
+                let remains = timeout.total.map(|total| total - (utils::now() - tic));
+                let encoding = parts
+                    .headers
+                    .get("content-encoding")
+                    .and_then(|t| t.to_str().ok());
+                let data = match within(remains, read_body(body_future, max_body)).await {
+                    Some(Ok(data)) => decode(data, encoding, max_body),
+                    Some(Err(kind)) => Err(kind),
+                    None => Err(FailureKind::Timeout(TimeoutKind::Total)),
+                };
+                let data = match data {
+                    Ok(data) => data,
+                    Err(kind) => {
+                        log::error!("Failed reading body: {:?} {}", mta.info.from, kind);
+                        mta.failure = Some(kind);
+                        return Err(mta);
                     }
-                    Err(_) => Err(mta),
-                    //Err(e) => Err(e.into()),
-                }
+                };
+                let body = Body::from(data);
+                let inn = InnerResponse {
+                    status: parts.status,
+                    version: parts.version,
+                    headers: parts.headers,
+                    extensions: Exts(ext_t, ext_p, Extensions::new(), parts.extensions),
+                };
+                mta.info.gap = toc - tic;
+                let ret = Response::from_parts(inn, body, mta);
+                Ok(ret)
             }
             Err(e) => {
-                if format!("{:?}", e).contains("Cancelled") {
-                    log::error!("Timeout request: {:?}", e);
-                } else {
-                    log::error!("Failed request: {:?}", e);
-                }
+                let kind = failure_kind(&e);
+                log::error!("Failed request: {:?} {} {:?}", mta.info.from, kind, e);
+                mta.failure = Some(kind);
                 Err(mta)
             }
         }
@@ -271,3 +483,88 @@ impl Client {
         join_all(i).await
     }
 }
+
+#[tokio::test]
+async fn test_client() {
+    use crate::component::{info::Timeout, Couple, Parsed, Task};
+    use std::io::Write;
+    fn parse(_: Response) -> Parsed<()> {
+        todo!()
+    }
+
+    // the first connection gets nothing, the second gets the head but never the whole body,
+    // the others get the whole `Response`
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    std::thread::spawn(move || {
+        let mut streams = Vec::new();
+        for (n, stream) in listener.incoming().enumerate() {
+            let mut stream = stream.unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = stream.read(&mut buf);
+            let head = b"HTTP/1.1 200 OK\r\ncontent-length: 10\r\n\r\nhello";
+            match n {
+                0 => {}
+                1 => stream.write_all(head).unwrap(),
+                _ => stream.write_all(&[&head[..], b"world"].concat()).unwrap(),
+            }
+            streams.push(stream);
+        }
+    });
+    let request = |addr: std::net::SocketAddr, timeout: Timeout, max_body: Option<usize>| {
+        let mut task = Task::get(format!("http://{}/", addr))
+            .timeout(timeout)
+            .parser(parse)
+            .body(Body::empty(), "marker")
+            .unwrap();
+        task.info_mut().max_body = max_body;
+        Request::from_couple(&Couple::new(task, None), None, None, None)
+    };
+    let failure = |result: Result<Response, MetaResponse>| match result {
+        Ok(_) => None,
+        Err(meta) => meta.failure,
+    };
+
+    let timeout = Timeout {
+        first_byte: Some(0.2),
+        total: Some(5.0),
+        ..Timeout::default()
+    };
+    let result = Client::new_plain()
+        .request(request(addr, timeout, None))
+        .await;
+    assert_eq!(
+        failure(result),
+        Some(FailureKind::Timeout(TimeoutKind::FirstByte))
+    );
+    let timeout = Timeout {
+        first_byte: Some(5.0),
+        total: Some(0.5),
+        ..Timeout::default()
+    };
+    let result = Client::new_plain()
+        .request(request(addr, timeout, None))
+        .await;
+    assert_eq!(
+        failure(result),
+        Some(FailureKind::Timeout(TimeoutKind::Total))
+    );
+    let req = request(addr, Timeout::default(), Some(4));
+    let result = Client::new_plain().request(req).await;
+    assert_eq!(failure(result), Some(FailureKind::TooLarge));
+
+    // nothing listening on it
+    let closed = "127.0.0.1:1".parse().unwrap();
+    let req = request(closed, Timeout::default(), None);
+    let result = Client::new_plain().request(req).await;
+    assert_eq!(failure(result), Some(FailureKind::ConnectRefused));
+
+    // `.invalid` is never resolved
+    let task = Task::get("http://dyer.invalid/")
+        .parser(parse)
+        .body(Body::empty(), "marker")
+        .unwrap();
+    let req = Request::from_couple(&Couple::new(task, None), None, None, None);
+    let result = Client::new_plain().request(req).await;
+    assert_eq!(failure(result), Some(FailureKind::Dns));
+}
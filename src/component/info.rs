@@ -1,6 +1,8 @@
 //! A structure that carries basic meta-data,
 //! including its origin, stime stamp, privilege, encoding and so on.
 //!
+use crate::component::retry::RetryPolicy;
+use crate::component::schedule::Schedule;
 use crate::{plugin::deser::*, utils};
 use http::Uri;
 use serde::{Deserialize, Serialize};
@@ -12,6 +14,10 @@ use serde::{Deserialize, Serialize};
 ///    `gap`, `created`, `able`
 /// - privilege
 ///    `rank`, `unique`, `used`
+/// - provenance
+///   `depth`, `parent`, `parent_uri`, `referer`
+/// - execution
+///   `timeout`, `max_body`, `retry`, `schedule`
 ///
 /// Some infomation must be specified, such as `marker`, `id`, and so on
 ///
@@ -47,6 +53,56 @@ pub struct Info {
     /// timestamp in seconds by which `entity` is allowed to be executed
     /// it is, as default, allowed when created
     pub able: f64,
+    /// distance from the seed, `0` for the `Task` from `entry_task`,
+    /// the `Task` yielded by parsing a `Response` is 1 deeper than it
+    #[serde(default)]
+    pub depth: u32,
+    /// id of the `Request` whose `Response` yields this `entity`, `0` if not yielded by parsing
+    #[serde(default)]
+    pub parent: u64,
+    /// uri of the `Response` that yields this `entity`
+    #[serde(default, with = "serde_uri_op")]
+    pub parent_uri: Option<Uri>,
+    /// set the `Referer` header as `parent_uri` when the `Request` is built, `false` as default,
+    /// it takes no effect if the `Task` already has one
+    #[serde(default)]
+    pub referer: bool,
+    /// timeouts of executing the `Request`, the unset ones fall back to those in `ArgApp`
+    #[serde(default)]
+    pub timeout: Timeout,
+    /// maximal bytes of the body of `Response`, both received and decompressed,
+    /// `None` falls back to `max_body` in `ArgApp`
+    #[serde(default)]
+    pub max_body: Option<usize>,
+    /// the policy to retry the failed `Request`, `None` falls back to `retry` in `ArgApp`
+    #[serde(default)]
+    pub retry: Option<RetryPolicy>,
+    /// the schedule to run the `Task` again after each success, `None` if it runs once
+    #[serde(default)]
+    pub schedule: Option<Schedule>,
+}
+
+/// timeouts in seconds of executing a `Request`, `None` means not set
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct Timeout {
+    /// duration to establish the connection, not applied when a pooled one is reused
+    pub connect: Option<f64>,
+    /// duration to receive the head of `Response` since the `Request` started,
+    /// including establishing the connection
+    pub first_byte: Option<f64>,
+    /// duration to receive the whole `Response`, including reading the body
+    pub total: Option<f64>,
+}
+
+impl Timeout {
+    /// fill the unset timeouts with those of `other`
+    pub fn or(self, other: Timeout) -> Timeout {
+        Timeout {
+            connect: self.connect.or(other.connect),
+            first_byte: self.first_byte.or(other.first_byte),
+            total: self.total.or(other.total),
+        }
+    }
 }
 
 impl Clone for Info {
@@ -62,6 +118,14 @@ impl Clone for Info {
             used: self.used,
             created: utils::now(),
             able: self.able,
+            depth: self.depth,
+            parent: self.parent,
+            parent_uri: self.parent_uri.clone(),
+            referer: self.referer,
+            timeout: self.timeout,
+            max_body: self.max_body,
+            retry: self.retry.clone(),
+            schedule: self.schedule.clone(),
         }
     }
 }
@@ -76,10 +140,18 @@ impl Default for Info {
             rank: 0,
             gap: 0.0,
             encoding: "utf-8".into(),
-            unique: false,
+            unique: true,
             used: 0,
             created: now,
             able: now,
+            depth: 0,
+            parent: 0,
+            parent_uri: None,
+            referer: false,
+            timeout: Timeout::default(),
+            max_body: None,
+            retry: None,
+            schedule: None,
         }
     }
 }
@@ -90,5 +162,30 @@ fn test_info() {
     assert_eq!(info.unique, true);
     assert_eq!(info.used, 0);
     assert_eq!(info.rank, 0);
+    assert_eq!(info.depth, 0);
+    assert_eq!(info.parent, 0);
+    assert!(info.parent_uri.is_none());
+    assert!(!info.referer);
     assert_eq!(info.encoding, "utf-8".to_string());
+
+    let timeout = Timeout {
+        connect: Some(1.0),
+        ..Timeout::default()
+    };
+    let fallback = Timeout {
+        connect: Some(5.0),
+        total: Some(30.0),
+        ..Timeout::default()
+    };
+    assert_eq!(info.timeout, Timeout::default());
+    assert!(info.max_body.is_none());
+    assert!(info.schedule.is_none());
+    assert_eq!(
+        timeout.or(fallback),
+        Timeout {
+            connect: Some(1.0),
+            first_byte: None,
+            total: Some(30.0),
+        }
+    );
 }
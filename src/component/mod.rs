@@ -1,4 +1,4 @@
-//! Instructions of components including [client], [affix], [request], [response], [task], [utils].
+//! Instructions of components including [client], [affix], [affinity], [request], [response], [retry], [schedule], [task], [utils].
 //!
 //! # OverView
 //!
@@ -12,11 +12,15 @@
 //! [now]: crate::component::utils::now
 //! [client]: crate::component::client
 //! [affix]: crate::component::affix
+//! [affinity]: crate::component::affinity
 //! [request]: crate::component::request
 //! [response]: crate::component::response
+//! [retry]: crate::component::retry
+//! [schedule]: crate::component::schedule
 //! [task]: crate::component::task
 //! [utils]: crate::component::utils
 //!
+pub mod affinity;
 pub mod affix;
 pub mod body;
 pub mod client;
@@ -28,6 +32,8 @@ pub mod parsed;
 pub mod proxy;
 pub mod request;
 pub mod response;
+pub mod retry;
+pub mod schedule;
 pub mod task;
 pub mod utils;
 #[cfg_attr(docsrs, doc(cfg(feature = "xpath-alpha")))]
@@ -162,7 +168,7 @@ pub use affix::Affix;
 #[doc(hidden)]
 pub use body::{Body, Chunk, Kind};
 #[doc(hidden)]
-pub use client::{Client, ClientType, CLIENTPOOL};
+pub use client::{Client, ClientType, Resolver, TimedConnector, CLIENTPOOL};
 #[doc(hidden)]
 pub use couple::Couple;
 pub use hyper::body::{Buf, Bytes};
@@ -177,6 +183,12 @@ pub use proxy::{Auth, AuthBasic, AuthBearer, AuthCustom, Proxy};
 #[doc(hidden)]
 pub use request::{Exts, InnerRequest, MetaRequest, Request, RequestBuilder};
 #[doc(hidden)]
-pub use response::{InnerResponse, MetaResponse, Response, ResponseBuilder};
+pub use response::{
+    FailureKind, InnerResponse, MetaResponse, Response, ResponseBuilder, TimeoutKind,
+};
+#[doc(hidden)]
+pub use retry::RetryPolicy;
+#[doc(hidden)]
+pub use schedule::Schedule;
 #[doc(hidden)]
 pub use task::{InnerTask, MetaTask, Task, TaskBuilder};
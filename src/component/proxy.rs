@@ -1,6 +1,6 @@
 //! A proxy that will re-route the request to
 //! Note that it currently supports `HTTP`
-use crate::client::{Client, ClientType, CLIENTPOOL};
+use crate::client::{Client, ClientType, Resolver, TimedConnector, CLIENTPOOL};
 use http::header::{HeaderName, HeaderValue};
 use hyper::client::HttpConnector;
 use hyper_proxy::{Intercept, Proxy as hProxy, ProxyConnector};
@@ -94,8 +94,8 @@ impl Proxy {
             let val = HeaderValue::from_str(&auth.encode()).unwrap();
             prx.set_header(name, val);
         }
-        let conn = HttpConnector::new();
-        let prxconn = ProxyConnector::from_proxy(conn, prx).unwrap();
+        let conn = HttpConnector::new_with_resolver(Resolver::new());
+        let prxconn = TimedConnector::new(ProxyConnector::from_proxy(conn, prx).unwrap());
         let client = hyper::Client::builder().build::<_, hyper::Body>(prxconn);
         let downloader = Client {
             id,
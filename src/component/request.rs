@@ -536,7 +536,7 @@ impl Request {
             >,
         >,
     ) -> Self {
-        match couple.affix {
+        let mut req = match couple.affix {
             Some(ref affix) => {
                 let item = Some(&affix.body);
                 let body = match f {
@@ -645,7 +645,16 @@ impl Request {
                     proxy,
                 }
             }
+        };
+        // set the Referer header if required and not set
+        if req.metar.info.referer && !req.inner.headers.contains_key(http::header::REFERER) {
+            if let Some(ref uri) = req.metar.info.parent_uri {
+                if let Ok(value) = HeaderValue::from_str(&uri.to_string()) {
+                    req.inner.headers.insert(http::header::REFERER, value);
+                }
+            }
         }
+        req
     }
 }
 
@@ -1218,3 +1227,21 @@ impl RequestBuilder {
         self.meta.body_fn = Some(body_fn);
     }
 }
+
+#[test]
+fn test_referer() {
+    let mut task = crate::Task::default();
+    task.inner.uri = "https://example.com/child".parse().unwrap();
+    task.metat.info.parent_uri = Some("https://example.com/parent".parse().unwrap());
+    let req = Request::from_couple(&Couple::new(task, None), None, None, None);
+    assert!(req.inner.headers.get(http::header::REFERER).is_none());
+
+    let mut task = crate::Task::default();
+    task.metat.info.parent_uri = Some("https://example.com/parent".parse().unwrap());
+    task.metat.info.referer = true;
+    let req = Request::from_couple(&Couple::new(task, None), None, None, None);
+    assert_eq!(
+        req.inner.headers.get(http::header::REFERER).unwrap(),
+        "https://example.com/parent"
+    );
+}
@@ -9,6 +9,7 @@ use crate::component::{body::Body, info::Info, request::MetaRequest, utils};
 use crate::plugin::deser::*;
 use crate::request::Exts;
 use http::{header::HeaderName, Extensions, HeaderMap, HeaderValue, StatusCode, Version};
+use serde::{Deserialize, Serialize};
 use std::{convert::TryFrom, fmt};
 
 /// An Wrapper of [http::Response]
@@ -63,10 +64,95 @@ pub struct MetaResponse {
     pub body_fn: Option<*const ()>,
     /// Whether a redirection happens or not
     pub redirected: bool,
+    /// the reason why the `Request` failed without `Response`, `None` if not failed
+    pub failure: Option<FailureKind>,
     /// additional arguments for extensive application
     pub exts: Exts,
 }
 
+/// the phase of executing a `Request` in which its timeout is exceeded,
+/// see [Timeout]
+///
+/// [Timeout]: crate::component::info::Timeout
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimeoutKind {
+    /// establishing the connection
+    Connect,
+    /// receiving the head of `Response`
+    FirstByte,
+    /// receiving the whole `Response`
+    Total,
+}
+
+/// the reason why a `Request` failed without `Response`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FailureKind {
+    /// the host name cannot be resolved
+    Dns,
+    /// the connection is refused by the server
+    ConnectRefused,
+    /// the TLS handshake failed
+    Tls,
+    /// its timeout is exceeded
+    Timeout(TimeoutKind),
+    /// cancelled by `App` for exceeding `join_gap`
+    Cancelled,
+    /// the body cannot be read completely
+    BodyRead,
+    /// the body cannot be decompressed according to `content-encoding`
+    Decode,
+    /// the body exceeds `Info.max_body`
+    TooLarge,
+    /// any other error
+    Other,
+}
+
+impl FailureKind {
+    /// the name of the failure, used as the key of `Stats.failures`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FailureKind::Dns => "dns",
+            FailureKind::ConnectRefused => "connect_refused",
+            FailureKind::Tls => "tls",
+            FailureKind::Timeout(TimeoutKind::Connect) => "timeout.connect",
+            FailureKind::Timeout(TimeoutKind::FirstByte) => "timeout.first_byte",
+            FailureKind::Timeout(TimeoutKind::Total) => "timeout.total",
+            FailureKind::Cancelled => "cancelled",
+            FailureKind::BodyRead => "body_read",
+            FailureKind::Decode => "decode",
+            FailureKind::TooLarge => "too_large",
+            FailureKind::Other => "other",
+        }
+    }
+}
+
+impl std::str::FromStr for FailureKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "dns" => Ok(FailureKind::Dns),
+            "connect_refused" => Ok(FailureKind::ConnectRefused),
+            "tls" => Ok(FailureKind::Tls),
+            "timeout.connect" => Ok(FailureKind::Timeout(TimeoutKind::Connect)),
+            "timeout.first_byte" => Ok(FailureKind::Timeout(TimeoutKind::FirstByte)),
+            "timeout.total" => Ok(FailureKind::Timeout(TimeoutKind::Total)),
+            "cancelled" => Ok(FailureKind::Cancelled),
+            "body_read" => Ok(FailureKind::BodyRead),
+            "decode" => Ok(FailureKind::Decode),
+            "too_large" => Ok(FailureKind::TooLarge),
+            "other" => Ok(FailureKind::Other),
+            _ => Err(format!("unknown failure kind: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for FailureKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 /// Safety: since *const () is a static function pointer(a usize that indicating hardware address)
 /// which is `Copy` so it owns the data, and no one else has it, the data can be safely transfered
 /// to another thread
@@ -84,6 +170,7 @@ impl Default for MetaResponse {
             body_fn: None,
             err_parser: None,
             redirected: false,
+            failure: None,
             exts: Exts::default(),
         }
     }
@@ -113,6 +200,7 @@ impl fmt::Debug for MetaResponse {
             .field("err_parser", &err_parser)
             .field("body_fn", &body_fn)
             .field("redirected", &self.redirected)
+            .field("failure", &self.failure)
             .field("exts", &self.exts)
             .finish()
     }
@@ -127,6 +215,7 @@ impl From<MetaRequest> for MetaResponse {
             err_parser: m.err_parser,
             body_fn: m.body_fn,
             redirected: false,
+            failure: None,
             exts: m.exts,
         }
     }
@@ -0,0 +1,149 @@
+//! The policy to retry a failed [Request], applied by the default err handler of [MiddleWare]
+//!
+//! A [Response] is retried if its status is listed in `statuses`, and a [Request] failed without
+//! [Response] is retried if its [FailureKind] is listed in `failures`, until `max_attempts` is
+//! reached. The retried one is available after an exponential backoff with jitter, or after the
+//! duration the `Retry-After` header asks for.
+//!
+//! The policy in [ArgApp] is used unless the [Task] has its own one.
+//!
+//! [Request]: crate::component::Request
+//! [Response]: crate::component::Response
+//! [Task]: crate::component::Task
+//! [MiddleWare]: crate::plugin::MiddleWare
+//! [ArgApp]: crate::engine::ArgApp
+use crate::component::response::{FailureKind, TimeoutKind};
+use http::{header::RETRY_AFTER, HeaderMap};
+use serde::{Deserialize, Serialize};
+
+/// the policy to retry a failed `Request`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// maximal number of attempts, including the first one
+    pub max_attempts: u32,
+    /// statuses of `Response` to retry
+    pub statuses: Vec<u16>,
+    /// kinds of failure to retry
+    pub failures: Vec<FailureKind>,
+    /// delay in seconds before the first retry, doubled for each retry after
+    pub backoff: f64,
+    /// maximal delay in seconds of the backoff
+    pub backoff_max: f64,
+    /// ratio of the delay randomly cut off, between `0.0` and `1.0`
+    pub jitter: f64,
+    /// wait as long as the `Retry-After` header asks for or not
+    pub retry_after: bool,
+}
+
+impl RetryPolicy {
+    /// create an instance of [RetryPolicy]
+    pub fn new() -> Self {
+        Self {
+            max_attempts: 3,
+            statuses: vec![429, 500, 502, 503, 504],
+            failures: vec![
+                FailureKind::ConnectRefused,
+                FailureKind::Timeout(TimeoutKind::Connect),
+                FailureKind::Timeout(TimeoutKind::FirstByte),
+                FailureKind::Timeout(TimeoutKind::Total),
+                FailureKind::Cancelled,
+                FailureKind::BodyRead,
+                FailureKind::Other,
+            ],
+            backoff: 1.0,
+            backoff_max: 60.0,
+            jitter: 0.5,
+            retry_after: true,
+        }
+    }
+
+    /// the `Response` of `status` should be retried or not
+    pub fn retry_status(&self, status: u16) -> bool {
+        self.statuses.contains(&status)
+    }
+
+    /// the `Request` failed of `kind` should be retried or not
+    pub fn retry_failure(&self, kind: FailureKind) -> bool {
+        self.failures.contains(&kind)
+    }
+
+    /// no attempt left after `used` retries or not
+    pub fn exhausted(&self, used: u32) -> bool {
+        used + 1 >= self.max_attempts
+    }
+
+    /// delay in seconds before the `retry`-th retry, starting from 1,
+    /// `rand` between `0.0` and `1.0` decides the jitter
+    ///
+    /// `retry_after` parsed from the `Retry-After` header takes precedence if respected
+    pub fn delay(&self, retry: u32, retry_after: Option<f64>, rand: f64) -> f64 {
+        if let (true, Some(secs)) = (self.retry_after, retry_after) {
+            return secs.max(0.0);
+        }
+        let exp = retry.saturating_sub(1).min(63) as i32;
+        let delay = (self.backoff * 2f64.powi(exp)).min(self.backoff_max);
+        let jitter = self.jitter.clamp(0.0, 1.0) * rand.clamp(0.0, 1.0);
+        (delay * (1.0 - jitter)).max(0.0)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// seconds to wait since `now` asked by the `Retry-After` header,
+/// either in seconds or a HTTP-date
+pub fn retry_after(headers: &HeaderMap, now: f64) -> Option<f64> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(secs as f64);
+    }
+    let date = httpdate::parse_http_date(value).ok()?;
+    let stamp = date
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()?
+        .as_secs_f64();
+    Some((stamp - now).max(0.0))
+}
+
+#[test]
+fn test_retry() {
+    use http::HeaderValue;
+
+    let policy = RetryPolicy {
+        jitter: 0.0,
+        ..RetryPolicy::new()
+    };
+    assert!(policy.retry_status(503));
+    assert!(!policy.retry_status(404));
+    assert!(policy.retry_failure(FailureKind::Timeout(TimeoutKind::Total)));
+    assert!(!policy.retry_failure(FailureKind::TooLarge));
+    assert!(!policy.exhausted(1));
+    assert!(policy.exhausted(2));
+
+    // doubled each time and capped
+    assert_eq!(policy.delay(1, None, 0.9), 1.0);
+    assert_eq!(policy.delay(3, None, 0.9), 4.0);
+    assert_eq!(policy.delay(10, None, 0.9), 60.0);
+    assert_eq!(policy.delay(2, Some(120.0), 0.9), 120.0);
+    let jittered = RetryPolicy::new();
+    assert_eq!(jittered.delay(2, None, 0.5), 1.5);
+    let ignored = RetryPolicy {
+        retry_after: false,
+        ..policy.clone()
+    };
+    assert_eq!(ignored.delay(2, Some(120.0), 0.0), 2.0);
+
+    let mut headers = HeaderMap::new();
+    assert_eq!(retry_after(&headers, 0.0), None);
+    headers.insert(RETRY_AFTER, HeaderValue::from_static("30"));
+    assert_eq!(retry_after(&headers, 0.0), Some(30.0));
+    headers.insert(
+        RETRY_AFTER,
+        HeaderValue::from_static("Thu, 01 Jan 1970 00:01:40 GMT"),
+    );
+    assert_eq!(retry_after(&headers, 40.0), Some(60.0));
+    assert_eq!(retry_after(&headers, 400.0), Some(0.0));
+}
@@ -0,0 +1,223 @@
+//! The schedule of a recurring [Task], which is put back into the queue after each success
+//!
+//! It is either a fixed interval in seconds since the last run, or a cron-like expression of
+//! five fields `minute hour day-of-month month day-of-week` in UTC. Each field is `*`, a number,
+//! a range `a-b`, a step `*/n` or `a-b/n`, or a list of them separated by `,`. Sunday is `0` or
+//! `7` in day-of-week. As cron does, a day matches if either day-of-month or day-of-week matches
+//! when both are restricted.
+//!
+//! ```rust
+//! # use dyer::schedule::Schedule;
+//! // at minute 0 and 30 of each hour from 9 to 17, Monday to Friday
+//! let schedule = Schedule::cron("0,30 9-17 * * 1-5").unwrap();
+//! // 2024-01-01 00:00:00 UTC, a Monday
+//! assert_eq!(schedule.next(1704067200.0), Some(1704067200.0 + 9.0 * 3600.0));
+//! ```
+//!
+//! [Task]: crate::component::Task
+use serde::{Deserialize, Serialize};
+
+/// the schedule of a recurring `Task`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub enum Schedule {
+    /// run again such seconds after the last run
+    Every(f64),
+    /// run at the minutes matched by the expression
+    Cron(Cron),
+}
+
+impl Schedule {
+    /// run every `secs` seconds
+    pub fn every(secs: f64) -> Self {
+        Schedule::Every(secs)
+    }
+
+    /// run at the minutes matched by the cron-like expression `expr`
+    pub fn cron(expr: &str) -> Result<Self, String> {
+        expr.parse().map(Schedule::Cron)
+    }
+
+    /// the time stamp of the next run after `now`,
+    /// `None` if nothing matches in the next 5 years
+    pub fn next(&self, now: f64) -> Option<f64> {
+        match self {
+            Schedule::Every(secs) => Some(now + secs.max(0.0)),
+            Schedule::Cron(cron) => cron.next(now),
+        }
+    }
+}
+
+/// minimal and maximal value of each field of the expression
+const BOUNDS: [(u32, u32); 5] = [(0, 59), (0, 23), (1, 31), (1, 12), (0, 7)];
+
+/// a cron-like expression of five fields, `minute hour day-of-month month day-of-week` in UTC
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cron {
+    expr: String,
+    /// matched values of each field as bits
+    fields: [u64; 5],
+    /// day-of-month and day-of-week are restricted or not
+    restricted: (bool, bool),
+}
+
+impl Cron {
+    /// `value` matches the `index`-th field or not
+    fn matches(&self, index: usize, value: u32) -> bool {
+        self.fields[index] & (1 << value) != 0
+    }
+
+    /// the day matches or not
+    fn day(&self, mday: u32, month: u32, wday: u32) -> bool {
+        if !self.matches(3, month) {
+            return false;
+        }
+        let (dom, dow) = (self.matches(2, mday), self.matches(4, wday));
+        match self.restricted {
+            (true, true) => dom || dow,
+            (true, false) => dom,
+            (false, true) => dow,
+            (false, false) => true,
+        }
+    }
+
+    /// the time stamp of the first matched minute after `now`
+    fn next(&self, now: f64) -> Option<f64> {
+        let start = (now / 60.0).floor() as i64 + 1;
+        let first = start.div_euclid(1440);
+        for days in first..first + 366 * 5 {
+            let (_, month, mday) = civil(days);
+            let wday = (days + 4).rem_euclid(7) as u32;
+            if !self.day(mday, month, wday) {
+                continue;
+            }
+            let from = if days == first {
+                start.rem_euclid(1440)
+            } else {
+                0
+            };
+            for minute in from..1440 {
+                if self.matches(1, (minute / 60) as u32) && self.matches(0, (minute % 60) as u32) {
+                    return Some(((days * 1440 + minute) * 60) as f64);
+                }
+            }
+        }
+        None
+    }
+}
+
+/// the year, month and day of `days` since 1970-01-01
+fn civil(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// parse a field of the expression bounded by `(min, max)` into bits
+fn field(expr: &str, (min, max): (u32, u32)) -> Result<u64, String> {
+    let num = |s: &str| {
+        s.parse::<u32>()
+            .map_err(|_| format!("invalid value {} in cron field {}", s, expr))
+    };
+    let mut bits = 0;
+    for part in expr.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, num(step)?),
+            None => (part, 1),
+        };
+        let (lo, hi) = match range.split_once('-') {
+            _ if range == "*" => (min, max),
+            Some((lo, hi)) => (num(lo)?, num(hi)?),
+            None if part.contains('/') => (num(range)?, max),
+            None => (num(range)?, num(range)?),
+        };
+        if step == 0 || lo < min || hi > max || lo > hi {
+            return Err(format!("invalid cron field {}", expr));
+        }
+        for value in (lo..=hi).step_by(step as usize) {
+            bits |= 1 << value;
+        }
+    }
+    Ok(bits)
+}
+
+impl std::str::FromStr for Cron {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts = s.split_whitespace().collect::<Vec<_>>();
+        if parts.len() != 5 {
+            return Err(format!("expect 5 fields in cron expression {}", s));
+        }
+        let mut fields = [0; 5];
+        for (i, part) in parts.iter().enumerate() {
+            fields[i] = field(part, BOUNDS[i])?;
+        }
+        // Sunday is either 0 or 7
+        if fields[4] & (1 << 7) != 0 {
+            fields[4] |= 1;
+        }
+        Ok(Cron {
+            expr: parts.join(" "),
+            fields,
+            restricted: (!parts[2].starts_with('*'), !parts[4].starts_with('*')),
+        })
+    }
+}
+
+impl std::fmt::Display for Cron {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.expr)
+    }
+}
+
+impl Serialize for Cron {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.expr)
+    }
+}
+
+impl<'de> Deserialize<'de> for Cron {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[test]
+fn test_schedule() {
+    // 2024-01-01 00:00:00 UTC, a Monday
+    let monday = 1704067200.0;
+    assert_eq!(civil(0), (1970, 1, 1));
+    assert_eq!(civil(19723), (2024, 1, 1));
+    assert_eq!(civil(19782), (2024, 2, 29));
+    assert_eq!(Schedule::every(300.0).next(monday), Some(monday + 300.0));
+
+    // strictly after now
+    let hourly = Schedule::cron("0 * * * *").unwrap();
+    assert_eq!(hourly.next(monday), Some(monday + 3600.0));
+    assert_eq!(hourly.next(monday - 1.0), Some(monday));
+    let quarter = Schedule::cron("*/15 * * * *").unwrap();
+    assert_eq!(quarter.next(monday + 61.0), Some(monday + 900.0));
+    // Sunday as 7, next Sunday at 03:30
+    let sunday = Schedule::cron("30 3 * * 7").unwrap();
+    assert_eq!(sunday.next(monday), Some(monday + 6.0 * 86400.0 + 12600.0));
+    // either day-of-month or day-of-week
+    let either = Schedule::cron("0 0 15 * 3").unwrap();
+    assert_eq!(either.next(monday), Some(monday + 2.0 * 86400.0));
+    // leap day
+    let leap = Schedule::cron("0 12 29 2 *").unwrap();
+    assert_eq!(leap.next(monday), Some(1709208000.0));
+
+    assert!(Schedule::cron("* * * *").is_err());
+    assert!(Schedule::cron("60 * * * *").is_err());
+    assert!(Schedule::cron("*/0 * * * *").is_err());
+    assert!(Schedule::cron("5-1 * * * *").is_err());
+    assert_eq!(Schedule::cron("0 0 30 2 *").unwrap().next(monday), None);
+}
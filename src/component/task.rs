@@ -9,7 +9,10 @@ use crate::component::proxy::{Auth, AuthBasic, AuthBearer, AuthCustom, Proxy};
 use crate::plugin::deser::*;
 use crate::utils;
 use crate::{
-    component::{Body, Info, Parsed},
+    component::{
+        affinity::Affinity, info::Timeout, retry::RetryPolicy, schedule::Schedule, Affix, Body,
+        Info, Parsed,
+    },
     Response,
 };
 use http::{
@@ -135,6 +138,9 @@ pub struct MetaTask {
     #[serde(serialize_with = "serde_fn::serfn_op")]
     #[serde(deserialize_with = "serde_fn::defn_op")]
     pub(crate) err_parser: Option<*const ()>,
+    /// the `Affix` it can be coupled with, any one if `None`
+    #[serde(default)]
+    pub affinity: Option<Affinity>,
     /// additional arguments for extensive application
     #[serde(skip)]
     pub exts: Extensions,
@@ -155,6 +161,7 @@ impl MetaTask {
             info: Info::default(),
             parser: 0 as *const (),
             err_parser: None,
+            affinity: None,
             exts: Extensions::new(),
         }
     }
@@ -176,6 +183,7 @@ impl fmt::Debug for MetaTask {
             .field("info", &self.info)
             .field("parser", &parser)
             .field("err_parser", &err_parser)
+            .field("affinity", &self.affinity)
             .field("exts", &self.exts)
             .finish()
     }
@@ -187,6 +195,7 @@ impl Default for MetaTask {
             info: Info::default(),
             parser: 0 as *const (),
             err_parser: None,
+            affinity: None,
             exts: Extensions::new(),
         }
     }
@@ -953,6 +962,48 @@ impl Task {
     pub fn into_parts(self) -> (InnerTask, Body, MetaTask, Option<Proxy>) {
         (self.inner, self.body, self.metat, self.proxy)
     }
+
+    /// the `Task` can be coupled with `affix` or not, see [Affinity]
+    pub fn accepts(&self, affix: &Affix) -> bool {
+        match self.metat.affinity {
+            Some(ref affinity) => affinity.accepts(self, affix),
+            None => true,
+        }
+    }
+
+    /// the `Task` to run again by its schedule after `now`,
+    /// `None` if it is not recurring or no run left
+    pub fn recur(mut self, now: f64) -> Option<Task> {
+        let info = &mut self.metat.info;
+        info.able = info.schedule.as_ref()?.next(now)?;
+        info.used = 0;
+        Some(self)
+    }
+
+    /// a copy of the `Task` to store, extensions are not copied as they are never stored
+    pub(crate) fn snapshot(&self) -> Task {
+        let mut info = self.metat.info.clone();
+        info.created = self.metat.info.created;
+        Task {
+            inner: InnerTask {
+                uri: self.inner.uri.clone(),
+                version: self.inner.version,
+                method: self.inner.method.clone(),
+                headers: self.inner.headers.clone(),
+                extensions: Extensions::new(),
+            },
+            body: self.body.clone(),
+            metat: MetaTask {
+                info,
+                parser: self.metat.parser,
+                err_parser: self.metat.err_parser,
+                affinity: self.metat.affinity.clone(),
+                exts: Extensions::new(),
+            },
+            #[cfg(feature = "proxy")]
+            proxy: self.proxy.clone(),
+        }
+    }
 }
 
 impl Hash for Task {
@@ -1435,6 +1486,118 @@ impl TaskBuilder {
         self
     }
 
+    /// set the timeouts of executing the `Task`, the unset ones fall back to those in `ArgApp`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use dyer::task::*;
+    /// # use dyer::info::Timeout;
+    /// # fn parser_fn(_: Response ) -> Parsed<E,> { todo!() }
+    /// let task = TaskBuilder::new()
+    ///     .timeout(Timeout { total: Some(20.0), ..Timeout::default() })
+    ///     .parser(parser_fn)
+    ///     .body(());
+    /// assert_eq!(task.info_ref().timeout.total, Some(20.0));
+    /// ```
+    pub fn timeout(mut self, timeout: Timeout) -> Self {
+        self.meta.info.timeout = timeout;
+        self
+    }
+
+    /// set the policy to retry the `Task` once failed, instead of the one in `ArgApp`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use dyer::task::*;
+    /// # use dyer::retry::RetryPolicy;
+    /// # fn parser_fn(_: Response ) -> Parsed<E,> { todo!() }
+    /// let task = TaskBuilder::new()
+    ///     .retry(RetryPolicy { max_attempts: 5, ..RetryPolicy::new() })
+    ///     .parser(parser_fn)
+    ///     .body(());
+    /// assert_eq!(task.info_ref().retry.unwrap().max_attempts, 5);
+    /// ```
+    pub fn retry(mut self, policy: RetryPolicy) -> Self {
+        self.meta.info.retry = Some(policy);
+        self
+    }
+
+    /// make the `Task` available `secs` seconds later from now
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use dyer::task::*;
+    /// # fn parser_fn(_: Response ) -> Parsed<E,> { todo!() }
+    /// let task = TaskBuilder::new()
+    ///     .delay(60.0)
+    ///     .parser(parser_fn)
+    ///     .body(());
+    /// assert!(task.info_ref().able > dyer::utils::now());
+    /// ```
+    pub fn delay(mut self, secs: f64) -> Self {
+        self.meta.info.able = utils::now() + secs.max(0.0);
+        self
+    }
+
+    /// make the `Task` available no earlier than the time stamp `stamp` in seconds
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use dyer::task::*;
+    /// # fn parser_fn(_: Response ) -> Parsed<E,> { todo!() }
+    /// let task = TaskBuilder::new()
+    ///     .not_before(1893456000.0)
+    ///     .parser(parser_fn)
+    ///     .body(());
+    /// assert_eq!(task.info_ref().able, 1893456000.0);
+    /// ```
+    pub fn not_before(mut self, stamp: f64) -> Self {
+        self.meta.info.able = stamp;
+        self
+    }
+
+    /// run the `Task` again by `schedule` after each success
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use dyer::task::*;
+    /// # use dyer::schedule::Schedule;
+    /// # fn parser_fn(_: Response ) -> Parsed<E,> { todo!() }
+    /// let task = TaskBuilder::new()
+    ///     .schedule(Schedule::every(600.0))
+    ///     .parser(parser_fn)
+    ///     .body(());
+    /// assert!(task.info_ref().schedule.is_some());
+    /// ```
+    pub fn schedule(mut self, schedule: Schedule) -> Self {
+        self.meta.info.schedule = Some(schedule);
+        self
+    }
+
+    /// couple the `Task` only with the `Affix` of `affinity`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use dyer::task::*;
+    /// # use dyer::affinity::Affinity;
+    /// # fn parser_fn(_: Response ) -> Parsed<E,> { todo!() }
+    /// let task = TaskBuilder::new()
+    ///     .affinity(Affinity::domain("example.com"))
+    ///     .parser(parser_fn)
+    ///     .body(());
+    /// assert!(task.meta_ref().affinity.is_some());
+    /// ```
+    pub fn affinity(mut self, affinity: Affinity) -> Self {
+        self.meta.affinity = Some(affinity);
+        self
+    }
+
     /// Take this `TaskBuilder` and combine the body to create a `Task`
     ///
     /// # Examples
@@ -5,6 +5,8 @@ use crate::engine::App;
 use crate::plugin::Affixor;
 use async_trait::async_trait;
 use std::error::Error;
+use std::marker::PhantomData;
+use std::ops::DerefMut;
 
 /// Although it starts a project, the work to do here is not very complicated
 /// it is as simple as setting up initial condition and other basic things.
@@ -34,4 +36,129 @@ where
 
     /// preparation before close [Actor]
     async fn close_actor(&mut self, app: &mut App<E>);
+
+    /// get the marker of `Self`, set as `Info.marker` of the [Task] it generates,
+    /// which is also the key of its stats
+    fn marker(&self) -> String {
+        crate::utils::type_name(self)
+    }
+}
+
+/// [Actor] whose [Affixor] is boxed, so that actors with different [Affixor] are driven by one
+/// [App]
+#[async_trait(?Send)]
+pub(crate) trait Erased<E> {
+    async fn entry_affix(&mut self) -> Option<Box<dyn Affixor + Send>>;
+
+    async fn entry_task(&mut self) -> Result<Vec<Task>, Box<dyn Error>>;
+
+    async fn open_actor(&mut self, app: &mut App<E>);
+
+    async fn close_actor(&mut self, app: &mut App<E>);
+
+    fn marker(&self) -> String;
+}
+
+/// wrap an owned or borrowed [Actor] into [Erased]
+pub(crate) struct Wrapper<T, A> {
+    inner: T,
+    _affixor: PhantomData<fn() -> A>,
+}
+
+impl<T, A> Wrapper<T, A> {
+    pub(crate) fn new(inner: T) -> Self {
+        Self {
+            inner,
+            _affixor: PhantomData,
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl<E, A, T> Erased<E> for Wrapper<T, A>
+where
+    T: DerefMut,
+    T::Target: Actor<E, A>,
+    A: Affixor + Send + 'static,
+{
+    async fn entry_affix(&mut self) -> Option<Box<dyn Affixor + Send>> {
+        match self.inner.entry_affix().await {
+            Some(affixor) => Some(Box::new(affixor)),
+            None => None,
+        }
+    }
+
+    async fn entry_task(&mut self) -> Result<Vec<Task>, Box<dyn Error>> {
+        let marker = self.inner.marker();
+        let mut tasks = self.inner.entry_task().await?;
+        for task in tasks.iter_mut() {
+            if task.metat.info.marker.is_empty() {
+                task.metat.info.marker = marker.clone();
+            }
+        }
+        Ok(tasks)
+    }
+
+    async fn open_actor(&mut self, app: &mut App<E>) {
+        self.inner.open_actor(app).await
+    }
+
+    async fn close_actor(&mut self, app: &mut App<E>) {
+        self.inner.close_actor(app).await
+    }
+
+    fn marker(&self) -> String {
+        self.inner.marker()
+    }
+}
+
+#[tokio::test]
+async fn test_actor() {
+    use crate::component::{Affix, MetaResponse, Response};
+    use crate::Request;
+
+    struct Site;
+    struct Empty;
+
+    #[async_trait]
+    impl Affixor for Empty {
+        async fn init(&mut self) {}
+        async fn invoke(&mut self) -> Option<Request> {
+            None
+        }
+        async fn after_invoke(&mut self) {}
+        async fn before_parse(&mut self, _: Option<&mut Result<Response, MetaResponse>>) {}
+        async fn parse(&mut self, _: Option<Result<Response, MetaResponse>>) -> Option<Affix> {
+            None
+        }
+        async fn after_parse(&mut self) {}
+        async fn close(&mut self) {}
+    }
+
+    #[async_trait]
+    impl Actor<(), Empty> for Site {
+        async fn new() -> Self {
+            Site
+        }
+        async fn entry_affix(&mut self) -> Option<Empty> {
+            Some(Empty)
+        }
+        async fn entry_task(&mut self) -> Result<Vec<Task>, Box<dyn Error>> {
+            let mut tasks = vec![Task::default(), Task::default()];
+            tasks[1].metat.info.marker = "other".into();
+            Ok(tasks)
+        }
+        async fn open_actor(&mut self, _: &mut App<()>) {}
+        async fn close_actor(&mut self, _: &mut App<()>) {}
+    }
+
+    let mut app = App::<()>::new();
+    app.add_actor(Site::new().await);
+    assert_eq!(app.actors.len(), 1);
+    let spd = app.actors[0].as_mut();
+    assert_eq!(spd.marker(), "Site");
+    assert!(spd.entry_affix().await.is_some());
+    let tasks = spd.entry_task().await.unwrap();
+    assert_eq!(tasks[0].metat.info.marker, "Site");
+    assert_eq!(tasks[1].metat.info.marker, "other");
 }
@@ -1,109 +1,239 @@
 use crate::utils;
-use crate::Client;
-use std::collections::{HashMap, LinkedList};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tokio::sync::Notify;
 use tokio::task::JoinHandle as Handle;
 
-// H hash of T serve as index for T
-// and linkedlist provodes positional infomation for certain hash H
+/// deadline of a future, the earliest is the greatest in `BinaryHeap`
+struct Deadline {
+    stamp: f64,
+    id: u64,
+    /// generation of the future spawned
+    gen: u64,
+}
+
+impl PartialEq for Deadline {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Deadline {}
+
+impl PartialOrd for Deadline {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Deadline {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .stamp
+            .total_cmp(&self.stamp)
+            .then_with(|| other.id.cmp(&self.id))
+    }
+}
+
+/// futures in flight indexed by id, each finished one reports its id through a channel,
+/// and the unfinished ones are aborted by their deadlines
+///
+/// A retried request is spawned again with the same id, so each spawn is tagged with a
+/// generation, the reports and deadlines of an earlier one are ignored.
 pub(crate) struct AppFut {
-    /// hash-value pairs
-    pub data: HashMap<u64, Handle<()>>,
-    /// an sorted(ascending) list storing hash and time stamp
-    pub index: LinkedList<(u64, f64)>,
+    /// handle and generation of each future in flight
+    data: HashMap<u64, (Handle<()>, u64)>,
+    /// deadlines, the ones of finished or aborted futures are skipped
+    deadlines: BinaryHeap<Deadline>,
+    /// generation of the next future spawned
+    gen: u64,
+    /// id and generation of finished futures
+    tx: UnboundedSender<(u64, u64)>,
+    rx: UnboundedReceiver<(u64, u64)>,
+    /// wake up the `App` once a future finished
+    wake: Arc<Notify>,
 }
 
 impl AppFut {
     /// create an instance
-    pub(crate) fn new() -> Self {
+    pub(crate) fn new(wake: Arc<Notify>) -> Self {
+        let (tx, rx) = unbounded_channel();
         Self {
             data: HashMap::new(),
-            index: LinkedList::new(),
+            deadlines: BinaryHeap::new(),
+            gen: 0,
+            tx,
+            rx,
+            wake,
         }
     }
 
-    /// directly take an value out and feed it to Client
-    /// and update `index`
-    pub(crate) async fn direct_join(&mut self, mut ids: Vec<u64>) {
-        let mut raw_results = Vec::with_capacity(ids.len());
-        ids.iter().for_each(|id| {
-            if let Some(item) = self.data.remove(id) {
-                raw_results.push(item);
-            }
+    /// number of futures in flight
+    pub(crate) fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// no future in flight or not
+    pub(crate) fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// spawn the future `fut` identified by `id`, aborted if unfinished by `deadline`,
+    /// the one of the same `id` still in flight is aborted
+    pub(crate) fn spawn<F>(&mut self, id: u64, deadline: f64, fut: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let gen = self.gen;
+        self.gen += 1;
+        let tx = self.tx.clone();
+        let wake = self.wake.clone();
+        let handle = tokio::spawn(async move {
+            fut.await;
+            let _ = tx.send((id, gen));
+            wake.notify_one();
         });
-        let mut item_cached = LinkedList::new();
-        while let Some(item) = self.index.pop_front() {
-            if ids.contains(&item.0) {
-                ids.retain(|&en| en != item.0);
-            } else {
-                item_cached.push_back(item);
-            }
-            if ids.is_empty() {
-                break;
+        if let Some((old, _)) = self.data.insert(id, (handle, gen)) {
+            log::debug!("Abort the earlier future of {} in flight", id);
+            old.abort();
+        }
+        self.deadlines.push(Deadline {
+            stamp: deadline,
+            id,
+            gen,
+        });
+        self.prune();
+    }
+
+    /// the future of `id` and `gen` is in flight or not
+    fn tracked(&self, id: u64, gen: u64) -> bool {
+        matches!(self.data.get(&id), Some((_, g)) if *g == gen)
+    }
+
+    /// remove the finished futures, return the number of them
+    pub(crate) fn reap(&mut self) -> usize {
+        let mut n = 0;
+        while let Ok((id, gen)) = self.rx.try_recv() {
+            if self.tracked(id, gen) {
+                self.data.remove(&id);
+                n += 1;
             }
         }
-        item_cached.append(&mut self.index);
-        self.index = item_cached;
-        Client::join_all(raw_results).await;
+        if n > 0 {
+            self.prune();
+        }
+        n
     }
 
-    /// execute results from `get_idel` and feed it to a callback
-    /// and update `index`
-    pub(crate) fn cancell(&mut self, gap: f64, capacity: usize) {
-        let idels = self.get_idel(gap, capacity);
-        if !idels.is_empty() {
+    /// the earliest deadline of futures in flight
+    pub(crate) fn next_deadline(&self) -> Option<f64> {
+        self.deadlines.peek().map(|en| en.stamp)
+    }
+
+    /// abort no more than `capacity` futures whose deadline is reached by `now`,
+    /// return the ids of them
+    pub(crate) fn cancel(&mut self, now: f64, capacity: usize) -> Vec<u64> {
+        let mut ids = Vec::new();
+        while ids.len() < capacity {
+            match self.deadlines.peek() {
+                Some(en) if en.stamp <= now => {}
+                _ => break,
+            }
+            let en = self.deadlines.pop().unwrap();
+            if self.tracked(en.id, en.gen) {
+                let (handle, _) = self.data.remove(&en.id).unwrap();
+                handle.abort();
+                ids.push(en.id);
+            }
+            self.prune();
+        }
+        if !ids.is_empty() {
             log::info!(
                 "cancelling {} / {} for Response.",
-                idels.len(),
-                self.index.len() + idels.len(),
+                ids.len(),
+                self.data.len() + ids.len(),
             );
-            idels.into_iter().for_each(|idel| (&idel.2).abort());
-            //.collect::<Vec<Handle<()>>>();
-            //Client::join_all(tasks).await;
         }
+        ids
     }
 
-    /// execute results from `get_idel` and feed it to a callback
-    /// and update `index`
-    pub(crate) async fn all(&mut self, gap: f64, capacity: usize) {
-        let idels = self.get_idel(gap, capacity);
-        if !idels.is_empty() {
+    /// join all in `timeout` seconds, the unfinished ones are aborted,
+    /// return the ids of aborted ones
+    pub(crate) async fn drain(&mut self, timeout: f64) -> Vec<u64> {
+        let deadline = utils::now() + timeout.max(0.0);
+        self.deadlines.clear();
+        let mut aborted = Vec::new();
+        for (id, (mut handle, _)) in self.data.drain() {
+            let remains = (deadline - utils::now()).max(0.0);
+            let duration = std::time::Duration::from_secs_f64(remains);
+            if tokio::time::timeout(duration, &mut handle).await.is_err() {
+                handle.abort();
+                aborted.push(id);
+            }
+        }
+        while self.rx.try_recv().is_ok() {}
+        if !aborted.is_empty() {
             log::info!(
-                "cancelling {} / {} for Response.",
-                idels.len(),
-                self.index.len() + idels.len(),
+                "{} future(s) unfinished in {} seconds",
+                aborted.len(),
+                timeout
             );
-            let tasks = idels
-                .into_iter()
-                .map(|idel| idel.2)
-                .collect::<Vec<Handle<()>>>();
-            Client::join_all(tasks).await;
         }
+        aborted
     }
 
-    /// inset an item and update `data` and `index`
-    pub(crate) fn insert(&mut self, item: Handle<()>, hash: u64, stamp: f64) {
-        self.data.insert(hash, item);
-        let now = utils::now();
-        self.index.push_back((hash, stamp));
-        assert!(self.index.front().unwrap_or(&(0, 0.0)).1 < now);
-    }
-
-    /// get no more than `capacity`s idels that longer than `gap`
-    pub(crate) fn get_idel(&mut self, gap: f64, capacity: usize) -> Vec<(u64, f64, Handle<()>)> {
-        let now = utils::now();
-        let mut items = Vec::with_capacity(capacity);
-        while let Some(item) = self.index.pop_front() {
-            if item.1 + gap < now && items.len() < capacity {
-                let ele = (item.0, item.1, self.data.remove(&item.0).unwrap());
-                items.push(ele);
-            } else {
-                self.index.push_front(item);
+    /// drop the deadlines of finished or aborted futures on the top,
+    /// rebuild the heap if too many of them remains
+    fn prune(&mut self) {
+        let data = &self.data;
+        let valid = |en: &Deadline| matches!(data.get(&en.id), Some((_, gen)) if *gen == en.gen);
+        while let Some(en) = self.deadlines.peek() {
+            if valid(en) {
                 break;
             }
+            self.deadlines.pop();
         }
-        if !items.is_empty() {
-            log::debug!("Availible response to parse: {}", items.len());
+        if self.deadlines.len() > 2 * self.data.len() + 64 {
+            let deadlines = std::mem::take(&mut self.deadlines);
+            self.deadlines = deadlines.into_iter().filter(|en| valid(en)).collect();
         }
-        items
     }
 }
+
+#[tokio::test]
+async fn test_appfut() {
+    let wake = Arc::new(Notify::new());
+    let mut futs = AppFut::new(wake.clone());
+    futs.spawn(1, 100.0, async {});
+    futs.spawn(2, 10.0, std::future::pending());
+    futs.spawn(3, 20.0, std::future::pending());
+    assert_eq!(futs.len(), 3);
+    assert_eq!(futs.next_deadline(), Some(10.0));
+
+    // the finished one is picked up
+    wake.notified().await;
+    assert_eq!(futs.reap(), 1);
+    assert_eq!(futs.len(), 2);
+
+    // aborted by the deadline
+    assert!(futs.cancel(5.0, 10).is_empty());
+    assert_eq!(futs.cancel(30.0, 1), vec![2]);
+    assert_eq!(futs.next_deadline(), Some(20.0));
+    assert_eq!(futs.drain(0.0).await, vec![3]);
+    assert!(futs.is_empty());
+    assert_eq!(futs.next_deadline(), None);
+
+    // a retry of the same id is not taken as finished by the earlier one
+    futs.spawn(4, 10.0, async {});
+    wake.notified().await;
+    futs.spawn(4, 100.0, std::future::pending());
+    assert_eq!(futs.reap(), 0);
+    assert_eq!(futs.len(), 1);
+    assert!(futs.cancel(50.0, 10).is_empty());
+    assert_eq!(futs.next_deadline(), Some(100.0));
+    assert_eq!(futs.cancel(100.0, 10), vec![4]);
+    assert!(futs.is_empty());
+}
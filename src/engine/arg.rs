@@ -11,11 +11,16 @@
 //! --- | --- | ---
 //! **`skip`** | [bool] | `true` as default, use the files stroed in `data_dir` or not, if not a new
 //! **`spawn_task_max`** | [usize] | `100` as default, the maximal length of spawned tasks
-//! **`buf_task`** | [usize] | `10000` as default, the length of `Task` collected by `parser`s, exceeding which all `Task` will be stored into `data_dir/tasks/` for memory saving
+//! **`buf_task`** | [usize] | `1000` as default, the length of `Task` collected by `parser`s, exceeding which they are moved into the queue of `Task`, the default [DiskFrontier] of which keeps at most `buf_task` ones in memory and spills the rest into `data_dir/tasks/` for memory saving
 //! **`round_entity`** | [usize] | `10` as default, the number of entities exceed which `process_entity` is called to consume them session will started all older files will be truncated.
 //! **`data_dir`** | [String] | `data/` as default, the place to store or load files of `App` when reaching` rate.cycle`
-//! **`nap`** | [f64] | `15.0` as default, the duration after which generated `Task` or `Affix` or recycled `Affix` become availible
-//! **`join_gap`** | [f64] | `7.0` as default, the duration which the spawned task exceeds the executor is called to forcefully join it
+//! **`nap`** | [f64] | `15.0` as default, the cooldown in seconds before the `Task` or `Affix` yielded by parsing a `Response` becomes available
+//! **`drain_timeout`** | [f64] | `30.0` as default, the duration in seconds to join the requests in flight when exiting on signal, the unfinished ones are aborted and stored
+//! **`join_gap`** | [f64] | `7.0` as default, the duration which the spawned task exceeds the executor is called to forcefully join it, extended to the total timeout of the `Request` if longer
+//! **`max_body`** | [usize] | `0` as default, the maximal bytes of the body of `Response`, both received and decompressed, `0` means unlimited, the exceeding one fails as `too_large`
+//! **`max_depth`** | [u32] | `0` as default, the `Task` deeper than it is dropped, `0` means unlimited
+//! **`referer`** | [bool] | `false` as default, set the `Referer` header of `Task` yielded by parsing as the uri of its parent `Response`
+//! **`aging`** | [f64] | `60.0` as default, the duration a queued `Task` or `Request` waits before its rank rises by 1, `0.0` disables aging
 //! **`round_req`** | [usize] | `10`, for more to see [ArgApp]
 //! **`round_req_min`** | [usize] | `5`, for more to see [ArgApp]
 //! **`round_req_max`** | [usize] | `77`, for more to see [ArgApp]
@@ -31,6 +36,86 @@
 //! **`arg_affix.is_on`** | [bool] | `false` as defalut, enable affix customization or not, when true, `Affixor` must be implemented
 //! **`arg_affix.affix_min`** | [usize] | `0` as default the minimal length of affix( including these in use or in future )
 //! **`arg_affix.affix_max`** | [usize] | `0` as default the minimal length of affix( including, these in use or in future )
+//! **`arg_affix.reuse`** | [u32] | `1` as default, the maximal number of uses of an `Affix` before it retires, `0` means unlimited
+//! **`arg_affix.max_failures`** | [u32] | `3` as default, the `Affix` failing so many times in a row is evicted, `0` means never
+//! **`arg_affix.max_bans`** | [u32] | `1` as default, the `Affix` banned so many times is evicted, `0` means never
+//! **`arg_affix.ban_statuses`** | [String] | `401 403 407` as default, space-separated statuses of `Response` taken as a ban of the `Affix`
+//!
+//! A used `Affix` cools down for `nap` seconds before reused, see [pool]
+//!
+//! ## ArgHost
+//!
+//! Argument | Type | Description
+//! --- | --- | ---
+//! **`host.by_domain`** | [bool] | `false` as default, group requests by registrable domain instead of host name
+//! **`host.concurrency`** | [usize] | `0` as default, the maximal number of requests in flight to each host, `0` means unlimited
+//! **`host.delay`** | [f64] | `0.0` as default, the minimal duration in seconds between two requests spawned to the same host
+//! **`host.rps`** | [f64] | `0.0` as default, the number of requests spawned to each host per second in the long run, `0.0` means unlimited
+//! **`host.burst`** | [f64] | `1.0` as default, the maximal number of requests spawned to each host at once under `host.rps`
+//! **`host.<host>.concurrency`** | [usize] | override `host.concurrency` for `<host>`, eg. `host.example.com.concurrency: 2`
+//! **`host.<host>.delay`** | [f64] | override `host.delay` for `<host>`, eg. `host.example.com.delay: 1.5`
+//! **`host.<host>.rps`** | [f64] | override `host.rps` for `<host>`
+//! **`host.<host>.burst`** | [f64] | override `host.burst` for `<host>`
+//!
+//! ## ArgThrottle
+//!
+//! Argument | Type | Description
+//! --- | --- | ---
+//! **`throttle.is_on`** | [bool] | `false` as default, adjust the delay and concurrency of each host from observed latency and errors, starting from those in `ArgHost`
+//! **`throttle.target`** | [f64] | `2.0` as default, the average number of requests in flight to each host the delay aims at
+//! **`throttle.delay_min`** | [f64] | `0.0` as default, the minimal delay in seconds of each host
+//! **`throttle.delay_max`** | [f64] | `60.0` as default, the maximal delay in seconds of each host
+//! **`throttle.concurrency_min`** | [usize] | `1` as default, the minimal concurrency of each host
+//! **`throttle.concurrency_max`** | [usize] | `16` as default, the maximal concurrency of each host
+//! **`throttle.error_rate`** | [f64] | `0.1` as default, the error rate above which the delay of a host no longer decreases and its concurrency no longer increases, `429` and `5xx` responses and failed requests count as errors
+//!
+//! ## Timeout
+//!
+//! Argument | Type | Description
+//! --- | --- | ---
+//! **`timeout.connect`** | [f64] | `0.0` as default, the duration in seconds to establish the connection, `0.0` means not set
+//! **`timeout.first_byte`** | [f64] | `0.0` as default, the duration in seconds to receive the head of `Response`, `0.0` means not set
+//! **`timeout.total`** | [f64] | `0.0` as default, the duration in seconds to receive the whole `Response`, `0.0` means not set
+//!
+//! They are the defaults of the timeouts of `Task`, see [Timeout]
+//!
+//! ## RetryPolicy
+//!
+//! Argument | Type | Description
+//! --- | --- | ---
+//! **`retry.max_attempts`** | [u32] | `3` as default, the maximal number of attempts of a `Request`, including the first one
+//! **`retry.statuses`** | [String] | `429 500 502 503 504` as default, space-separated statuses of `Response` to retry
+//! **`retry.failures`** | [String] | `connect_refused timeout.connect timeout.first_byte timeout.total cancelled body_read other` as default, space-separated kinds of failure to retry
+//! **`retry.backoff`** | [f64] | `1.0` as default, the delay in seconds before the first retry, doubled for each retry after
+//! **`retry.backoff_max`** | [f64] | `60.0` as default, the maximal delay in seconds of the backoff
+//! **`retry.jitter`** | [f64] | `0.5` as default, the ratio of the delay randomly cut off
+//! **`retry.retry_after`** | [bool] | `true` as default, wait as long as the `Retry-After` header asks for instead of the backoff
+//!
+//! They are the defaults of the policy of `Task`, see [RetryPolicy]
+//!
+//! ## ArgBudget
+//!
+//! Argument | Type | Description
+//! --- | --- | ---
+//! **`budget.requests`** | [usize] | `0` as default, the maximal number of `Request` spawned, `0` means unlimited
+//! **`budget.responses`** | [usize] | `0` as default, the maximal number of `Response` received, `0` means unlimited
+//! **`budget.entities`** | [usize] | `0` as default, the maximal number of entities produced, `0` means unlimited
+//! **`budget.errors`** | [usize] | `0` as default, the maximal number of failed requests and `Response` of status `4xx` or `5xx`, `0` means unlimited
+//! **`budget.bytes`** | [usize] | `0` as default, the maximal number of body bytes received, `0` means unlimited
+//! **`budget.wall`** | [f64] | `0.0` as default, the maximal duration in seconds since the `App` starts running, `0.0` means unlimited
+//!
+//! Once any of them is exhausted, the `App` stops gracefully as stopped by [Handle], and
+//! `Summary.budget` tells which one.
+//!
+//! ## ArgDupe
+//!
+//! Argument | Type | Description
+//! --- | --- | ---
+//! **`dupe.is_on`** | [bool] | `true` as default, drop the `Task` already seen if its `Info.unique` is `true`
+//! **`dupe.mode`** | [String] | `exact` as default, `exact` keeps all fingerprints in memory, `bloom` keeps them in a bloom filter with bounded memory
+//! **`dupe.headers`** | [String] | empty as default, space-separated header names taken into the fingerprint, eg. `dupe.headers: accept-language cookie`
+//! **`dupe.capacity`** | [usize] | `1000000` as default, the expected number of fingerprints in `bloom` mode
+//! **`dupe.fp_rate`** | [f64] | `0.001` as default, the false positive rate in `bloom` mode
 //!
 //! ## ArgRate
 //!
@@ -41,11 +126,27 @@
 //! **`rate.rate_low`** | [f64] | 0.333 as dafault, a value between 0-1.0 that lower the taks to be spawned, eg. the oringnal value is 12, rate_low is 0.33, the tasks to be spawned is 12.0 * 0.33 ~ 4.
 //! **`rate.err`** | [usize] | the nubmer that erros of `Response` occurs, the default value is 0,
 //! **`rate.interval`** | [f64] | the duration of time after which updating `ArgRate` `ArgApp`, the default value is 30.0,
+//! **`rate.rps`** | [f64] | `0.0` as default, the number of requests spawned per second in the long run, `0.0` means unlimited, once set it replaces the load model above
+//! **`rate.burst`** | [f64] | `1.0` as default, the maximal number of requests spawned at once under `rate.rps`
+//!
+//! `rate.rps` and `host.rps` are enforced by token buckets, see [limiter]
 //!
 //! [ArgApp]: crate::engine::arg::ArgApp
+//! [Timeout]: crate::component::info::Timeout
+//! [RetryPolicy]: crate::component::retry::RetryPolicy
+//! [limiter]: crate::engine::limiter
+//! [pool]: crate::engine::pool
+//! [Handle]: crate::engine::Handle
+//! [DiskFrontier]: crate::engine::frontier::DiskFrontier
 //!
+use crate::component::info::Timeout;
+use crate::component::response::FailureKind;
+use crate::component::retry::RetryPolicy;
+use crate::engine::dupe::DupeMode;
+use crate::engine::stats::{Budget, Stats};
 use crate::engine::vault::Vault;
 use crate::utils;
+use std::collections::HashMap;
 use std::io::{BufRead, BufReader};
 
 /// Arguments that control the [App] at runtime, including using history or not,  
@@ -59,7 +160,7 @@ use std::io::{BufRead, BufReader};
 /// [App]: crate::App
 #[derive(std::fmt::Debug)]
 pub struct ArgApp {
-    /// time tap added to created Tasks or Affixs
+    /// cooldown in seconds before the `Task` or `Affix` yielded by parsing becomes available
     pub nap: f64,
     /// gap to forcefully join the spawned task
     pub join_gap: f64,
@@ -83,6 +184,17 @@ pub struct ArgApp {
     pub round_errs: usize,
     ///consume Entity once upon a time
     pub round_entity: usize,
+    /// duration in seconds to join the requests in flight when exiting on signal
+    pub drain_timeout: f64,
+    /// maximal bytes of the body of `Response`, `None` means unlimited
+    pub max_body: Option<usize>,
+    /// the `Task` deeper than it is dropped, `0` means unlimited
+    pub max_depth: u32,
+    /// set the `Referer` header of `Task` yielded by parsing as the uri of its parent `Response`
+    pub referer: bool,
+    /// duration in seconds a queued `Task` or `Request` waits before its rank rises by 1,
+    /// `0.0` disables aging
+    pub aging: f64,
     /// use files in directory `data/` or not,
     /// set true as default
     pub skip: bool,
@@ -90,6 +202,16 @@ pub struct ArgApp {
     pub(crate) rate: Vault<ArgRate>,
     /// control the affix workflow
     pub arg_affix: Option<ArgAffix>,
+    /// limit the concurrency and pace towards each host
+    pub arg_host: ArgHost,
+    /// drop the duplicate `Task`
+    pub arg_dupe: ArgDupe,
+    /// limits of a run, exceeding which stops the `App`
+    pub arg_budget: ArgBudget,
+    /// default timeouts of executing the `Request`
+    pub timeout: Timeout,
+    /// default policy to retry the failed `Request`
+    pub retry: RetryPolicy,
     /// directory that store history file
     pub data_dir: String,
 }
@@ -98,7 +220,7 @@ impl ArgApp {
     /// create an instance of [ArgApp]
     pub fn new() -> Self {
         let mut arg = ArgApp {
-            nap: 17.0,
+            nap: 15.0,
             join_gap: 7.0,
             round_req: 10,
             round_req_min: 3,
@@ -110,9 +232,19 @@ impl ArgApp {
             round_res: 10,
             round_errs: 10,
             round_entity: 10,
+            drain_timeout: 30.0,
+            max_body: None,
+            max_depth: 0,
+            referer: false,
+            aging: 60.0,
             skip: true,
             rate: Vault::new(ArgRate::new()),
             arg_affix: None,
+            arg_host: ArgHost::new(),
+            arg_dupe: ArgDupe::new(),
+            arg_budget: ArgBudget::new(),
+            timeout: Timeout::default(),
+            retry: RetryPolicy::new(),
             data_dir: "data/".into(),
         };
         arg.parse_config(None, false);
@@ -239,6 +371,51 @@ impl ArgApp {
                     panic!("Update Failed, invalid value for round_entity: {}", value);
                 }
             }
+            "drain_timeout" => {
+                if let Ok(v) = value.parse::<f64>() {
+                    self.drain_timeout = v;
+                } else if fail_safe {
+                    log::error!("Update Failed, invalid value for drain_timeout: {}", value);
+                } else {
+                    panic!("Update Failed, invalid value for drain_timeout: {}", value);
+                }
+            }
+            "max_body" => {
+                if let Ok(v) = value.parse::<usize>() {
+                    self.max_body = Some(v).filter(|v| *v > 0);
+                } else if fail_safe {
+                    log::error!("Update Failed, invalid value for max_body: {}", value);
+                } else {
+                    panic!("Update Failed, invalid value for max_body: {}", value);
+                }
+            }
+            "max_depth" => {
+                if let Ok(v) = value.parse::<u32>() {
+                    self.max_depth = v;
+                } else if fail_safe {
+                    log::error!("Update Failed, invalid value for max_depth: {}", value);
+                } else {
+                    panic!("Update Failed, invalid value for max_depth: {}", value);
+                }
+            }
+            "referer" => {
+                if let Ok(v) = value.parse::<bool>() {
+                    self.referer = v;
+                } else if fail_safe {
+                    log::error!("Update Failed, invalid value for referer: {}", value);
+                } else {
+                    panic!("Update Failed, invalid value for referer: {}", value);
+                }
+            }
+            "aging" => {
+                if let Ok(v) = value.parse::<f64>() {
+                    self.aging = v;
+                } else if fail_safe {
+                    log::error!("Update Failed, invalid value for aging: {}", value);
+                } else {
+                    panic!("Update Failed, invalid value for aging: {}", value);
+                }
+            }
             "skip" => {
                 if let Ok(v) = value.parse::<bool>() {
                     self.skip = v;
@@ -293,6 +470,24 @@ impl ArgApp {
                     panic!("Update Failed, invalid value for rate.remains: {}", value);
                 }
             }
+            "rate.rps" => {
+                if let Ok(v) = value.parse::<f64>() {
+                    self.rate.as_mut().rps = v;
+                } else if fail_safe {
+                    log::error!("Update Failed, invalid value for rate.rps: {}", value);
+                } else {
+                    panic!("Update Failed, invalid value for rate.rps: {}", value);
+                }
+            }
+            "rate.burst" => {
+                if let Ok(v) = value.parse::<f64>() {
+                    self.rate.as_mut().burst = v;
+                } else if fail_safe {
+                    log::error!("Update Failed, invalid value for rate.burst: {}", value);
+                } else {
+                    panic!("Update Failed, invalid value for rate.burst: {}", value);
+                }
+            }
             "rate.rate_low" => {
                 if let Ok(v) = value.parse::<f64>() {
                     self.rate.as_mut().rate_low = v;
@@ -401,6 +596,507 @@ impl ArgApp {
                     self.arg_affix = Some(arg);
                 }
             }
+            "arg_affix.reuse" => {
+                if let Ok(v) = value.parse::<u32>() {
+                    self.arg_affix.get_or_insert_with(ArgAffix::new).reuse = v;
+                } else if fail_safe {
+                    log::error!(
+                        "Update Failed, invalid value for arg_affix.reuse: {}",
+                        value
+                    );
+                } else {
+                    panic!(
+                        "Update Failed, invalid value for arg_affix.reuse: {}",
+                        value
+                    );
+                }
+            }
+            "arg_affix.max_failures" => {
+                if let Ok(v) = value.parse::<u32>() {
+                    self.arg_affix
+                        .get_or_insert_with(ArgAffix::new)
+                        .max_failures = v;
+                } else if fail_safe {
+                    log::error!(
+                        "Update Failed, invalid value for arg_affix.max_failures: {}",
+                        value
+                    );
+                } else {
+                    panic!(
+                        "Update Failed, invalid value for arg_affix.max_failures: {}",
+                        value
+                    );
+                }
+            }
+            "arg_affix.max_bans" => {
+                if let Ok(v) = value.parse::<u32>() {
+                    self.arg_affix.get_or_insert_with(ArgAffix::new).max_bans = v;
+                } else if fail_safe {
+                    log::error!(
+                        "Update Failed, invalid value for arg_affix.max_bans: {}",
+                        value
+                    );
+                } else {
+                    panic!(
+                        "Update Failed, invalid value for arg_affix.max_bans: {}",
+                        value
+                    );
+                }
+            }
+            "arg_affix.ban_statuses" => {
+                let parsed = value
+                    .split_whitespace()
+                    .map(|v| v.parse::<u16>())
+                    .collect::<Result<Vec<_>, _>>();
+                if let Ok(v) = parsed {
+                    self.arg_affix
+                        .get_or_insert_with(ArgAffix::new)
+                        .ban_statuses = v;
+                } else if fail_safe {
+                    log::error!(
+                        "Update Failed, invalid value for arg_affix.ban_statuses: {}",
+                        value
+                    );
+                } else {
+                    panic!(
+                        "Update Failed, invalid value for arg_affix.ban_statuses: {}",
+                        value
+                    );
+                }
+            }
+            "host.by_domain" => {
+                if let Ok(v) = value.parse::<bool>() {
+                    self.arg_host.by_domain = v;
+                } else if fail_safe {
+                    log::error!("Update Failed, invalid value for host.by_domain: {}", value);
+                } else {
+                    panic!("Update Failed, invalid value for host.by_domain: {}", value);
+                }
+            }
+            "host.concurrency" => {
+                if let Ok(v) = value.parse::<usize>() {
+                    self.arg_host.concurrency = v;
+                } else if fail_safe {
+                    log::error!(
+                        "Update Failed, invalid value for host.concurrency: {}",
+                        value
+                    );
+                } else {
+                    panic!(
+                        "Update Failed, invalid value for host.concurrency: {}",
+                        value
+                    );
+                }
+            }
+            "host.delay" => {
+                if let Ok(v) = value.parse::<f64>() {
+                    self.arg_host.delay = v;
+                } else if fail_safe {
+                    log::error!("Update Failed, invalid value for host.delay: {}", value);
+                } else {
+                    panic!("Update Failed, invalid value for host.delay: {}", value);
+                }
+            }
+            "host.rps" => {
+                if let Ok(v) = value.parse::<f64>() {
+                    self.arg_host.rps = v;
+                } else if fail_safe {
+                    log::error!("Update Failed, invalid value for host.rps: {}", value);
+                } else {
+                    panic!("Update Failed, invalid value for host.rps: {}", value);
+                }
+            }
+            "host.burst" => {
+                if let Ok(v) = value.parse::<f64>() {
+                    self.arg_host.burst = v;
+                } else if fail_safe {
+                    log::error!("Update Failed, invalid value for host.burst: {}", value);
+                } else {
+                    panic!("Update Failed, invalid value for host.burst: {}", value);
+                }
+            }
+            "throttle.is_on" => {
+                if let Ok(v) = value.parse::<bool>() {
+                    self.arg_host.throttle.is_on = v;
+                } else if fail_safe {
+                    log::error!("Update Failed, invalid value for throttle.is_on: {}", value);
+                } else {
+                    panic!("Update Failed, invalid value for throttle.is_on: {}", value);
+                }
+            }
+            "throttle.target" => {
+                if let Ok(v) = value.parse::<f64>() {
+                    self.arg_host.throttle.target = v;
+                } else if fail_safe {
+                    log::error!(
+                        "Update Failed, invalid value for throttle.target: {}",
+                        value
+                    );
+                } else {
+                    panic!(
+                        "Update Failed, invalid value for throttle.target: {}",
+                        value
+                    );
+                }
+            }
+            "throttle.delay_min" => {
+                if let Ok(v) = value.parse::<f64>() {
+                    self.arg_host.throttle.delay_min = v;
+                } else if fail_safe {
+                    log::error!(
+                        "Update Failed, invalid value for throttle.delay_min: {}",
+                        value
+                    );
+                } else {
+                    panic!(
+                        "Update Failed, invalid value for throttle.delay_min: {}",
+                        value
+                    );
+                }
+            }
+            "throttle.delay_max" => {
+                if let Ok(v) = value.parse::<f64>() {
+                    self.arg_host.throttle.delay_max = v;
+                } else if fail_safe {
+                    log::error!(
+                        "Update Failed, invalid value for throttle.delay_max: {}",
+                        value
+                    );
+                } else {
+                    panic!(
+                        "Update Failed, invalid value for throttle.delay_max: {}",
+                        value
+                    );
+                }
+            }
+            "throttle.concurrency_min" => {
+                if let Ok(v) = value.parse::<usize>() {
+                    self.arg_host.throttle.concurrency_min = v;
+                } else if fail_safe {
+                    log::error!(
+                        "Update Failed, invalid value for throttle.concurrency_min: {}",
+                        value
+                    );
+                } else {
+                    panic!(
+                        "Update Failed, invalid value for throttle.concurrency_min: {}",
+                        value
+                    );
+                }
+            }
+            "throttle.concurrency_max" => {
+                if let Ok(v) = value.parse::<usize>() {
+                    self.arg_host.throttle.concurrency_max = v;
+                } else if fail_safe {
+                    log::error!(
+                        "Update Failed, invalid value for throttle.concurrency_max: {}",
+                        value
+                    );
+                } else {
+                    panic!(
+                        "Update Failed, invalid value for throttle.concurrency_max: {}",
+                        value
+                    );
+                }
+            }
+            "throttle.error_rate" => {
+                if let Ok(v) = value.parse::<f64>() {
+                    self.arg_host.throttle.error_rate = v;
+                } else if fail_safe {
+                    log::error!(
+                        "Update Failed, invalid value for throttle.error_rate: {}",
+                        value
+                    );
+                } else {
+                    panic!(
+                        "Update Failed, invalid value for throttle.error_rate: {}",
+                        value
+                    );
+                }
+            }
+            "timeout.connect" => {
+                if let Ok(v) = value.parse::<f64>() {
+                    self.timeout.connect = Some(v).filter(|v| *v > 0.0);
+                } else if fail_safe {
+                    log::error!(
+                        "Update Failed, invalid value for timeout.connect: {}",
+                        value
+                    );
+                } else {
+                    panic!(
+                        "Update Failed, invalid value for timeout.connect: {}",
+                        value
+                    );
+                }
+            }
+            "timeout.first_byte" => {
+                if let Ok(v) = value.parse::<f64>() {
+                    self.timeout.first_byte = Some(v).filter(|v| *v > 0.0);
+                } else if fail_safe {
+                    log::error!(
+                        "Update Failed, invalid value for timeout.first_byte: {}",
+                        value
+                    );
+                } else {
+                    panic!(
+                        "Update Failed, invalid value for timeout.first_byte: {}",
+                        value
+                    );
+                }
+            }
+            "timeout.total" => {
+                if let Ok(v) = value.parse::<f64>() {
+                    self.timeout.total = Some(v).filter(|v| *v > 0.0);
+                } else if fail_safe {
+                    log::error!("Update Failed, invalid value for timeout.total: {}", value);
+                } else {
+                    panic!("Update Failed, invalid value for timeout.total: {}", value);
+                }
+            }
+            "retry.max_attempts" => {
+                if let Ok(v) = value.parse::<u32>() {
+                    self.retry.max_attempts = v;
+                } else if fail_safe {
+                    log::error!(
+                        "Update Failed, invalid value for retry.max_attempts: {}",
+                        value
+                    );
+                } else {
+                    panic!(
+                        "Update Failed, invalid value for retry.max_attempts: {}",
+                        value
+                    );
+                }
+            }
+            "retry.statuses" => {
+                let parsed = value
+                    .split_whitespace()
+                    .map(|v| v.parse::<u16>())
+                    .collect::<Result<Vec<_>, _>>();
+                if let Ok(v) = parsed {
+                    self.retry.statuses = v;
+                } else if fail_safe {
+                    log::error!("Update Failed, invalid value for retry.statuses: {}", value);
+                } else {
+                    panic!("Update Failed, invalid value for retry.statuses: {}", value);
+                }
+            }
+            "retry.failures" => {
+                let parsed = value
+                    .split_whitespace()
+                    .map(|v| v.parse::<FailureKind>())
+                    .collect::<Result<Vec<_>, _>>();
+                if let Ok(v) = parsed {
+                    self.retry.failures = v;
+                } else if fail_safe {
+                    log::error!("Update Failed, invalid value for retry.failures: {}", value);
+                } else {
+                    panic!("Update Failed, invalid value for retry.failures: {}", value);
+                }
+            }
+            "retry.backoff" => {
+                if let Ok(v) = value.parse::<f64>() {
+                    self.retry.backoff = v;
+                } else if fail_safe {
+                    log::error!("Update Failed, invalid value for retry.backoff: {}", value);
+                } else {
+                    panic!("Update Failed, invalid value for retry.backoff: {}", value);
+                }
+            }
+            "retry.backoff_max" => {
+                if let Ok(v) = value.parse::<f64>() {
+                    self.retry.backoff_max = v;
+                } else if fail_safe {
+                    log::error!(
+                        "Update Failed, invalid value for retry.backoff_max: {}",
+                        value
+                    );
+                } else {
+                    panic!(
+                        "Update Failed, invalid value for retry.backoff_max: {}",
+                        value
+                    );
+                }
+            }
+            "retry.jitter" => {
+                if let Ok(v) = value.parse::<f64>() {
+                    self.retry.jitter = v;
+                } else if fail_safe {
+                    log::error!("Update Failed, invalid value for retry.jitter: {}", value);
+                } else {
+                    panic!("Update Failed, invalid value for retry.jitter: {}", value);
+                }
+            }
+            "retry.retry_after" => {
+                if let Ok(v) = value.parse::<bool>() {
+                    self.retry.retry_after = v;
+                } else if fail_safe {
+                    log::error!(
+                        "Update Failed, invalid value for retry.retry_after: {}",
+                        value
+                    );
+                } else {
+                    panic!(
+                        "Update Failed, invalid value for retry.retry_after: {}",
+                        value
+                    );
+                }
+            }
+            "dupe.is_on" => {
+                if let Ok(v) = value.parse::<bool>() {
+                    self.arg_dupe.is_on = v;
+                } else if fail_safe {
+                    log::error!("Update Failed, invalid value for dupe.is_on: {}", value);
+                } else {
+                    panic!("Update Failed, invalid value for dupe.is_on: {}", value);
+                }
+            }
+            "dupe.mode" => {
+                if let Ok(v) = value.parse::<DupeMode>() {
+                    self.arg_dupe.mode = v;
+                } else if fail_safe {
+                    log::error!("Update Failed, invalid value for dupe.mode: {}", value);
+                } else {
+                    panic!("Update Failed, invalid value for dupe.mode: {}", value);
+                }
+            }
+            "dupe.headers" => {
+                self.arg_dupe.headers = value
+                    .split_whitespace()
+                    .map(|name| name.to_lowercase())
+                    .collect();
+            }
+            "dupe.capacity" => {
+                if let Ok(v) = value.parse::<usize>() {
+                    self.arg_dupe.capacity = v;
+                } else if fail_safe {
+                    log::error!("Update Failed, invalid value for dupe.capacity: {}", value);
+                } else {
+                    panic!("Update Failed, invalid value for dupe.capacity: {}", value);
+                }
+            }
+            "dupe.fp_rate" => {
+                if let Ok(v) = value.parse::<f64>() {
+                    self.arg_dupe.fp_rate = v;
+                } else if fail_safe {
+                    log::error!("Update Failed, invalid value for dupe.fp_rate: {}", value);
+                } else {
+                    panic!("Update Failed, invalid value for dupe.fp_rate: {}", value);
+                }
+            }
+            "budget.requests" => {
+                if let Ok(v) = value.parse::<usize>() {
+                    self.arg_budget.requests = v;
+                } else if fail_safe {
+                    log::error!(
+                        "Update Failed, invalid value for budget.requests: {}",
+                        value
+                    );
+                } else {
+                    panic!(
+                        "Update Failed, invalid value for budget.requests: {}",
+                        value
+                    );
+                }
+            }
+            "budget.responses" => {
+                if let Ok(v) = value.parse::<usize>() {
+                    self.arg_budget.responses = v;
+                } else if fail_safe {
+                    log::error!(
+                        "Update Failed, invalid value for budget.responses: {}",
+                        value
+                    );
+                } else {
+                    panic!(
+                        "Update Failed, invalid value for budget.responses: {}",
+                        value
+                    );
+                }
+            }
+            "budget.entities" => {
+                if let Ok(v) = value.parse::<usize>() {
+                    self.arg_budget.entities = v;
+                } else if fail_safe {
+                    log::error!(
+                        "Update Failed, invalid value for budget.entities: {}",
+                        value
+                    );
+                } else {
+                    panic!(
+                        "Update Failed, invalid value for budget.entities: {}",
+                        value
+                    );
+                }
+            }
+            "budget.errors" => {
+                if let Ok(v) = value.parse::<usize>() {
+                    self.arg_budget.errors = v;
+                } else if fail_safe {
+                    log::error!("Update Failed, invalid value for budget.errors: {}", value);
+                } else {
+                    panic!("Update Failed, invalid value for budget.errors: {}", value);
+                }
+            }
+            "budget.bytes" => {
+                if let Ok(v) = value.parse::<usize>() {
+                    self.arg_budget.bytes = v;
+                } else if fail_safe {
+                    log::error!("Update Failed, invalid value for budget.bytes: {}", value);
+                } else {
+                    panic!("Update Failed, invalid value for budget.bytes: {}", value);
+                }
+            }
+            "budget.wall" => {
+                if let Ok(v) = value.parse::<f64>() {
+                    self.arg_budget.wall = v;
+                } else if fail_safe {
+                    log::error!("Update Failed, invalid value for budget.wall: {}", value);
+                } else {
+                    panic!("Update Failed, invalid value for budget.wall: {}", value);
+                }
+            }
+            _ if key.starts_with("host.") => {
+                // per host override, eg. `host.example.com.delay`
+                let updated = match key["host.".len()..].rsplit_once('.') {
+                    Some((host, "concurrency")) => value
+                        .parse::<usize>()
+                        .map(|v| {
+                            let limit = self.arg_host.overrides.entry(host.into()).or_default();
+                            limit.concurrency = Some(v);
+                        })
+                        .is_ok(),
+                    Some((host, "delay")) => value
+                        .parse::<f64>()
+                        .map(|v| {
+                            let limit = self.arg_host.overrides.entry(host.into()).or_default();
+                            limit.delay = Some(v);
+                        })
+                        .is_ok(),
+                    Some((host, "rps")) => value
+                        .parse::<f64>()
+                        .map(|v| {
+                            let limit = self.arg_host.overrides.entry(host.into()).or_default();
+                            limit.rps = Some(v);
+                        })
+                        .is_ok(),
+                    Some((host, "burst")) => value
+                        .parse::<f64>()
+                        .map(|v| {
+                            let limit = self.arg_host.overrides.entry(host.into()).or_default();
+                            limit.burst = Some(v);
+                        })
+                        .is_ok(),
+                    _ => false,
+                };
+                if !updated {
+                    if fail_safe {
+                        log::error!("Update Failed, invalid value for {}: {}", key, value);
+                    } else {
+                        panic!("Update Failed, invalid value for {}: {}", key, value);
+                    }
+                }
+            }
             _ => {
                 eprintln!("Unrecognizable or unnecessary variable: {}", key);
             }
@@ -415,15 +1111,26 @@ impl ArgApp {
             "arg_affix.is_on",
             "arg_affix.affix_min",
             "arg_affix.affix_max",
+            "arg_affix.reuse",
+            "arg_affix.max_failures",
+            "arg_affix.max_bans",
+            "arg_affix.ban_statuses",
             "rate.cycle",
             "rate.interval",
             "rate.load",
             "rate.remains",
             "rate.rate_low",
+            "rate.rps",
+            "rate.burst",
             "data_dir",
             "skip",
             "nap",
             "join_gap",
+            "drain_timeout",
+            "max_body",
+            "max_depth",
+            "referer",
+            "aging",
             "round_req",
             "round_req_min",
             "round_req_max",
@@ -434,7 +1141,42 @@ impl ArgApp {
             "round_res",
             "round_errs",
             "round_entity",
+            "host.by_domain",
+            "host.concurrency",
+            "host.delay",
+            "host.rps",
+            "host.burst",
+            "throttle.is_on",
+            "throttle.target",
+            "throttle.delay_min",
+            "throttle.delay_max",
+            "throttle.concurrency_min",
+            "throttle.concurrency_max",
+            "throttle.error_rate",
+            "timeout.connect",
+            "timeout.first_byte",
+            "timeout.total",
+            "retry.max_attempts",
+            "retry.statuses",
+            "retry.failures",
+            "retry.backoff",
+            "retry.backoff_max",
+            "retry.jitter",
+            "retry.retry_after",
+            "dupe.is_on",
+            "dupe.mode",
+            "dupe.headers",
+            "dupe.capacity",
+            "dupe.fp_rate",
+            "budget.requests",
+            "budget.responses",
+            "budget.entities",
+            "budget.errors",
+            "budget.bytes",
+            "budget.wall",
         ];
+        // per host overrides removed from the file no longer apply
+        self.arg_host.overrides.clear();
         let file = std::fs::File::open("dyer.cfg").unwrap();
         let reader = BufReader::new(file);
         reader.lines().filter(|line| line.is_ok()).for_each(|line| {
@@ -445,7 +1187,7 @@ impl ArgApp {
                 .collect::<Vec<String>>();
             if pairs.len() == 2 {
                 let key = pairs[0].trim();
-                if fields.contains(&key) {
+                if fields.contains(&key) || key.starts_with("host.") {
                     let value = pairs[1].trim().trim_end_matches(|c| c == ',');
                     self.set(key, value, fail_safe);
                 }
@@ -481,6 +1223,14 @@ pub struct ArgAffix {
     pub affix_min: usize,
     /// maximal cached affix number(including affixs used in `Request` that to be executed)
     pub affix_max: usize,
+    /// maximal number of uses of an affix, `0` means unlimited
+    pub reuse: u32,
+    /// number of failures in a row after which an affix is evicted, `0` means never
+    pub max_failures: u32,
+    /// number of bans after which an affix is evicted, `0` means never
+    pub max_bans: u32,
+    /// statuses of `Response` taken as a ban of the affix
+    pub ban_statuses: Vec<u16>,
 }
 
 impl ArgAffix {
@@ -490,10 +1240,233 @@ impl ArgAffix {
             is_on: false,
             affix_min: 0,
             affix_max: 0,
+            reuse: 1,
+            max_failures: 3,
+            max_bans: 1,
+            ban_statuses: vec![401, 403, 407],
+        }
+    }
+}
+
+/// To control the filter that drops the duplicate [Task]
+///
+/// [Task]: crate::Task
+#[derive(std::fmt::Debug)]
+pub struct ArgDupe {
+    /// use the filter or not
+    pub is_on: bool,
+    /// the way that fingerprints are recorded
+    pub mode: DupeMode,
+    /// names of headers taken into the fingerprint, lowercased
+    pub headers: Vec<String>,
+    /// the expected number of fingerprints in bloom mode
+    pub capacity: usize,
+    /// the false positive rate in bloom mode
+    pub fp_rate: f64,
+}
+
+impl ArgDupe {
+    /// create an instance of [ArgDupe]
+    pub fn new() -> Self {
+        ArgDupe {
+            is_on: true,
+            mode: DupeMode::Exact,
+            headers: Vec::new(),
+            capacity: 1_000_000,
+            fp_rate: 0.001,
+        }
+    }
+}
+
+impl Default for ArgDupe {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Limits of a run of [App], exceeding any of which stops it gracefully,
+/// `0` or `0.0` means unlimited
+///
+/// [App]: crate::App
+#[derive(std::fmt::Debug, Default, Clone)]
+pub struct ArgBudget {
+    /// maximal number of `Request` spawned
+    pub requests: usize,
+    /// maximal number of `Response` received
+    pub responses: usize,
+    /// maximal number of entities produced
+    pub entities: usize,
+    /// maximal number of failed requests and `Response` of status `4xx` or `5xx`
+    pub errors: usize,
+    /// maximal number of body bytes received
+    pub bytes: usize,
+    /// maximal duration in seconds since the `App` starts running
+    pub wall: f64,
+}
+
+impl ArgBudget {
+    /// create an instance of [ArgBudget], all unlimited
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// the first budget exhausted by `stats` at `now` if any
+    pub fn exhausted(&self, stats: &Stats, now: f64) -> Option<Budget> {
+        let responses = stats.responses.values().sum::<usize>();
+        let reached = |limit: usize, used: usize| limit > 0 && used >= limit;
+        if reached(self.requests, stats.requests) {
+            Some(Budget::Requests)
+        } else if reached(self.responses, responses) {
+            Some(Budget::Responses)
+        } else if reached(self.entities, stats.entities) {
+            Some(Budget::Entities)
+        } else if reached(self.errors, stats.errors()) {
+            Some(Budget::Errors)
+        } else if reached(self.bytes, stats.bytes) {
+            Some(Budget::Bytes)
+        } else if self.wall > 0.0 && now - stats.started >= self.wall {
+            Some(Budget::Wall)
+        } else {
+            None
+        }
+    }
+
+    /// number of `Request` allowed to be spawned after `stats`
+    pub fn requests_left(&self, stats: &Stats) -> usize {
+        match self.requests {
+            0 => usize::MAX,
+            n => n.saturating_sub(stats.requests),
+        }
+    }
+
+    /// the time stamp by which `wall` is exhausted if limited
+    pub fn deadline(&self, stats: &Stats) -> Option<f64> {
+        if self.wall > 0.0 {
+            Some(stats.started + self.wall)
+        } else {
+            None
         }
     }
 }
 
+/// To control the concurrency and pace of requests towards each host,
+/// requests exceeding the limit are kept in cache until the host is available.
+#[derive(std::fmt::Debug)]
+pub struct ArgHost {
+    /// group requests by registrable domain instead of host name
+    pub by_domain: bool,
+    /// maximal number of requests in flight to each host, `0` means unlimited
+    pub concurrency: usize,
+    /// minimal duration in seconds between two requests spawned to the same host
+    pub delay: f64,
+    /// requests spawned to each host per second in the long run, `0.0` means unlimited
+    pub rps: f64,
+    /// maximal number of requests spawned to each host at once under `rps`
+    pub burst: f64,
+    /// settings for specific host that override the default ones
+    pub overrides: HashMap<String, HostLimit>,
+    /// adjust the limit of each host at runtime
+    pub throttle: ArgThrottle,
+}
+
+/// limit of a specific host, `None` falls back to the default in [ArgHost]
+#[derive(std::fmt::Debug, Default, Clone)]
+pub struct HostLimit {
+    /// maximal number of requests in flight to the host
+    pub concurrency: Option<usize>,
+    /// minimal duration in seconds between two requests spawned to the host
+    pub delay: Option<f64>,
+    /// requests spawned to the host per second in the long run
+    pub rps: Option<f64>,
+    /// maximal number of requests spawned to the host at once
+    pub burst: Option<f64>,
+}
+
+impl ArgHost {
+    /// create an instance of [ArgHost]
+    pub fn new() -> Self {
+        ArgHost {
+            by_domain: false,
+            concurrency: 0,
+            delay: 0.0,
+            rps: 0.0,
+            burst: 1.0,
+            overrides: HashMap::new(),
+            throttle: ArgThrottle::new(),
+        }
+    }
+
+    /// get the concurrency and delay applied to `host`
+    pub fn limit(&self, host: &str) -> (usize, f64) {
+        match self.overrides.get(host) {
+            Some(limit) => (
+                limit.concurrency.unwrap_or(self.concurrency),
+                limit.delay.unwrap_or(self.delay),
+            ),
+            None => (self.concurrency, self.delay),
+        }
+    }
+
+    /// get the rps and burst applied to `host`
+    pub fn rate(&self, host: &str) -> (f64, f64) {
+        match self.overrides.get(host) {
+            Some(limit) => (
+                limit.rps.unwrap_or(self.rps),
+                limit.burst.unwrap_or(self.burst),
+            ),
+            None => (self.rps, self.burst),
+        }
+    }
+}
+
+impl Default for ArgHost {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// To adjust the delay and concurrency of each host from observed latency and errors,
+/// within the bounds, starting from the limit in [ArgHost]
+#[derive(std::fmt::Debug, Clone)]
+pub struct ArgThrottle {
+    /// enable auto-throttle or not
+    pub is_on: bool,
+    /// average number of requests in flight to each host the delay aims at
+    pub target: f64,
+    /// minimal delay in seconds of each host
+    pub delay_min: f64,
+    /// maximal delay in seconds of each host
+    pub delay_max: f64,
+    /// minimal concurrency of each host
+    pub concurrency_min: usize,
+    /// maximal concurrency of each host
+    pub concurrency_max: usize,
+    /// error rate above which the delay no longer decreases and the concurrency no longer
+    /// increases
+    pub error_rate: f64,
+}
+
+impl ArgThrottle {
+    /// create an instance of [ArgThrottle]
+    pub fn new() -> Self {
+        ArgThrottle {
+            is_on: false,
+            target: 2.0,
+            delay_min: 0.0,
+            delay_max: 60.0,
+            concurrency_min: 1,
+            concurrency_max: 16,
+            error_rate: 0.1,
+        }
+    }
+}
+
+impl Default for ArgThrottle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// some infomation about `dyer` at rumtime where speed and error-handler based on
 #[derive(std::fmt::Debug)]
 pub struct ArgRate {
@@ -519,6 +1492,11 @@ pub struct ArgRate {
     pub anchor: f64,
     /// vector of gap each request takes to receive response header in each interval  
     pub stamps: Vec<f64>,
+    /// requests spawned per second in the long run, `0.0` means unlimited,
+    /// once set the load model above is bypassed
+    pub rps: f64,
+    /// maximal number of requests spawned at once under `rps`
+    pub burst: f64,
 }
 
 impl ArgRate {
@@ -536,6 +1514,8 @@ impl ArgRate {
             anchor: now + 30.0,
             interval: 30.0,
             stamps: Vec::new(),
+            rps: 0.0,
+            burst: 1.0,
         }
     }
 
@@ -572,6 +1552,14 @@ impl ArgRate {
         false
     }
 
+    /// the time stamp after `now` by which more [Task] are allowed to be spawned
+    ///
+    /// [Task]: crate::Task
+    pub fn next_able(&self, now: f64) -> f64 {
+        let gap = self.interval / self.load.max(1.0);
+        self.anchor.min(now + gap)
+    }
+
     /// decide the length of [Task] to be spawned
     ///
     /// [Task]: crate::Task
@@ -601,3 +1589,29 @@ impl ArgRate {
         }
     }
 }
+
+#[test]
+fn test_budget() {
+    let mut stats = Stats::new();
+    stats.started = 100.0;
+    let mut budget = ArgBudget::new();
+    stats.request("");
+    stats.response("", 503, 10, 1.0);
+    assert_eq!(budget.exhausted(&stats, 1e9), None);
+    assert_eq!(budget.requests_left(&stats), usize::MAX);
+    assert_eq!(budget.deadline(&stats), None);
+
+    budget.wall = 60.0;
+    budget.errors = 2;
+    budget.requests = 3;
+    assert_eq!(budget.requests_left(&stats), 2);
+    assert_eq!(budget.deadline(&stats), Some(160.0));
+    assert_eq!(budget.exhausted(&stats, 120.0), None);
+    assert_eq!(budget.exhausted(&stats, 160.0), Some(Budget::Wall));
+    stats.failure("", FailureKind::Dns, 1);
+    assert_eq!(budget.exhausted(&stats, 120.0), Some(Budget::Errors));
+    stats.request("");
+    stats.request("");
+    assert_eq!(budget.requests_left(&stats), 0);
+    assert_eq!(budget.exhausted(&stats, 120.0), Some(Budget::Requests));
+}
@@ -0,0 +1,466 @@
+//! Filter that drops the repeated [Task] before it enters [App],
+//!
+//! Each [Task] whose `Info.unique` is `true` is fingerprinted, the ones already seen are dropped.
+//! By default, the fingerprint is computed from the method, canonical uri, selected headers and
+//! body of the [Task], customize it with [App::fingerprint].
+//!
+//! Two modes are available, see [DupeMode] and [ArgDupe] for more.
+//!
+//! The recorded fingerprints are stored into `data_dir/dupe` at backup and exit on signal or stop,
+//! and reloaded when resuming the session. The default fingerprint is FNV-1a over the bytes of
+//! each part, so that it stays the same across Rust releases, and the stored file is tagged with
+//! a version, the file of another version is refused.
+//!
+//! [Task]: crate::component::Task
+//! [App]: crate::engine::App
+//! [App::fingerprint]: crate::engine::App::fingerprint
+//! [ArgDupe]: crate::engine::arg::ArgDupe
+use crate::component::Task;
+use crate::engine::arg::ArgDupe;
+use http::Uri;
+use std::collections::HashSet;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+
+/// the way that fingerprints are recorded
+#[derive(std::fmt::Debug, Clone, Copy, PartialEq)]
+pub enum DupeMode {
+    /// every fingerprint is kept in memory, no false positive
+    Exact,
+    /// fingerprints are kept in a bloom filter with bounded memory,
+    /// a small portion of new `Task` may be taken as duplicate
+    Bloom,
+}
+
+impl std::str::FromStr for DupeMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "exact" => Ok(DupeMode::Exact),
+            "bloom" => Ok(DupeMode::Bloom),
+            _ => Err(format!("unknown dupe mode: {}", s)),
+        }
+    }
+}
+
+/// normalize the uri so that the equivalent ones are the same,
+/// scheme and host are lowercased, default port removed, query parameters sorted
+pub fn canonical_uri(uri: &Uri) -> String {
+    let scheme = uri.scheme_str().unwrap_or("http").to_lowercase();
+    let host = uri.host().unwrap_or("").to_lowercase();
+    let port = match (uri.port_u16(), scheme.as_str()) {
+        (None, _) | (Some(80), "http") | (Some(443), "https") => "".to_string(),
+        (Some(port), _) => format!(":{}", port),
+    };
+    let path = if uri.path().is_empty() {
+        "/"
+    } else {
+        uri.path()
+    };
+    let mut pairs = uri
+        .query()
+        .unwrap_or("")
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .collect::<Vec<_>>();
+    pairs.sort_unstable();
+    if pairs.is_empty() {
+        format!("{}://{}{}{}", scheme, host, port, path)
+    } else {
+        format!("{}://{}{}{}?{}", scheme, host, port, path, pairs.join("&"))
+    }
+}
+
+/// version of the stored fingerprints, bumped whenever the default fingerprint
+/// or the format changes
+const VERSION: u8 = 1;
+
+/// 64-bit FNV-1a hash, whose algorithm is fixed unlike `DefaultHasher`
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn new() -> Self {
+        Fnv1a(0xcbf29ce484222325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+
+    /// write a part prefixed by its length, so that adjacent parts never run together
+    fn part(&mut self, bytes: &[u8]) {
+        self.write(&(bytes.len() as u64).to_le_bytes());
+        self.write(bytes);
+    }
+}
+
+/// default fingerprint of [Task] from its method, canonical uri, body
+/// and the headers whose name in `headers`
+///
+/// [Task]: crate::component::Task
+pub fn fingerprint(task: &Task, headers: &[String]) -> u64 {
+    let mut hasher = Fnv1a::new();
+    hasher.part(task.inner.method.as_str().as_bytes());
+    hasher.part(canonical_uri(&task.inner.uri).as_bytes());
+    for name in headers {
+        if let Some(value) = task.inner.headers.get(name.as_str()) {
+            hasher.part(name.as_bytes());
+            hasher.part(value.as_bytes());
+        }
+    }
+    let len = task.body.iter().map(|chunk| chunk.len()).sum::<usize>();
+    hasher.write(&(len as u64).to_le_bytes());
+    task.body.iter().for_each(|chunk| hasher.write(chunk));
+    hasher.0
+}
+
+/// a bloom filter of `u64` fingerprints
+#[derive(std::fmt::Debug)]
+pub(crate) struct Bloom {
+    bits: Vec<u64>,
+    /// number of bits
+    size: u64,
+    /// number of hash functions
+    hashes: u32,
+}
+
+impl Bloom {
+    /// create a bloom filter holding `capacity` items with false positive rate `fp_rate`
+    pub(crate) fn new(capacity: usize, fp_rate: f64) -> Self {
+        let capacity = capacity.max(1) as f64;
+        let fp_rate = fp_rate.clamp(1e-9, 0.5);
+        let ln2 = std::f64::consts::LN_2;
+        let size = (-capacity * fp_rate.ln() / (ln2 * ln2)).ceil().max(64.0) as u64;
+        let hashes = ((size as f64 / capacity) * ln2).round().max(1.0) as u32;
+        Self {
+            bits: vec![0; size.div_ceil(64) as usize],
+            size,
+            hashes,
+        }
+    }
+
+    /// positions of the bits of `fp` via double hashing
+    fn positions(&self, fp: u64) -> impl Iterator<Item = u64> {
+        // mix the fingerprint to get a second independent hash
+        let mut h2 = fp ^ (fp >> 33);
+        h2 = h2.wrapping_mul(0xff51afd7ed558ccd);
+        h2 ^= h2 >> 33;
+        let h2 = h2 | 1;
+        let size = self.size;
+        (0..self.hashes as u64).map(move |i| fp.wrapping_add(i.wrapping_mul(h2)) % size)
+    }
+
+    pub(crate) fn contains(&self, fp: u64) -> bool {
+        self.positions(fp)
+            .all(|pos| self.bits[(pos / 64) as usize] & (1 << (pos % 64)) != 0)
+    }
+
+    /// insert `fp`, `false` returned if it is seemingly present
+    pub(crate) fn insert(&mut self, fp: u64) -> bool {
+        let mut fresh = false;
+        let positions = self.positions(fp).collect::<Vec<_>>();
+        for pos in positions {
+            let bit = 1 << (pos % 64);
+            let word = &mut self.bits[(pos / 64) as usize];
+            if *word & bit == 0 {
+                fresh = true;
+                *word |= bit;
+            }
+        }
+        fresh
+    }
+}
+
+/// customized fingerprint of [Task]
+///
+/// [Task]: crate::component::Task
+pub type Fingerprint = dyn Fn(&Task) -> u64 + Send;
+
+/// the fingerprints recorded in [DupeFilter]
+#[derive(std::fmt::Debug)]
+pub(crate) enum Seen {
+    Exact(HashSet<u64>),
+    Bloom(Bloom),
+}
+
+/// Filter that records the fingerprints of [Task] and drops the repeated ones
+///
+/// [Task]: crate::component::Task
+pub struct DupeFilter {
+    /// the filter is on or not
+    pub(crate) is_on: bool,
+    pub(crate) mode: DupeMode,
+    pub(crate) capacity: usize,
+    pub(crate) fp_rate: f64,
+    /// names of headers taken into the default fingerprint
+    pub(crate) headers: Vec<String>,
+    pub(crate) seen: Seen,
+    /// number of fingerprints recorded
+    pub(crate) len: usize,
+    /// number of dropped `Task`
+    pub dropped: usize,
+    /// customized fingerprint function
+    pub(crate) fingerprint: Option<Box<Fingerprint>>,
+}
+
+impl DupeFilter {
+    /// create an instance of [DupeFilter]
+    pub fn new(arg: &ArgDupe) -> Self {
+        let seen = match arg.mode {
+            DupeMode::Exact => Seen::Exact(HashSet::new()),
+            DupeMode::Bloom => Seen::Bloom(Bloom::new(arg.capacity, arg.fp_rate)),
+        };
+        Self {
+            is_on: arg.is_on,
+            mode: arg.mode,
+            capacity: arg.capacity,
+            fp_rate: arg.fp_rate,
+            headers: arg.headers.clone(),
+            seen,
+            len: 0,
+            dropped: 0,
+            fingerprint: None,
+        }
+    }
+
+    /// follow the updated [ArgDupe], the recorded fingerprints are kept when possible
+    pub fn configure(&mut self, arg: &ArgDupe) {
+        self.is_on = arg.is_on;
+        self.headers = arg.headers.clone();
+        if self.mode == arg.mode && self.capacity == arg.capacity && self.fp_rate == arg.fp_rate {
+            return;
+        }
+        let mut filter = Self::new(arg);
+        match &self.seen {
+            Seen::Exact(set) => set.iter().for_each(|fp| {
+                filter.insert(*fp);
+            }),
+            Seen::Bloom(_) => {
+                log::warn!("Fingerprints in bloom filter cannot be migrated, start over");
+            }
+        }
+        filter.dropped = self.dropped;
+        filter.fingerprint = self.fingerprint.take();
+        *self = filter;
+    }
+
+    /// number of fingerprints recorded
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// no fingerprint recorded or not
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// get the fingerprint of `task`
+    pub fn fingerprint_of(&self, task: &Task) -> u64 {
+        match self.fingerprint {
+            Some(ref ff) => ff(task),
+            None => fingerprint(task, &self.headers),
+        }
+    }
+
+    /// the fingerprint is recorded or not
+    pub fn contains(&self, fp: u64) -> bool {
+        match &self.seen {
+            Seen::Exact(set) => set.contains(&fp),
+            Seen::Bloom(bloom) => bloom.contains(fp),
+        }
+    }
+
+    /// record the fingerprint, `false` returned if it is already recorded
+    pub fn insert(&mut self, fp: u64) -> bool {
+        let fresh = match &mut self.seen {
+            Seen::Exact(set) => set.insert(fp),
+            Seen::Bloom(bloom) => bloom.insert(fp),
+        };
+        if fresh {
+            self.len += 1;
+        }
+        fresh
+    }
+
+    /// check the `task`, `false` returned if it is a duplicate and should be dropped
+    pub fn check(&mut self, task: &Task) -> bool {
+        if !self.is_on || !task.metat.info.unique {
+            return true;
+        }
+        let fp = self.fingerprint_of(task);
+        if self.insert(fp) {
+            return true;
+        }
+        self.dropped += 1;
+        log::debug!("Drop duplicate task: {}", task.inner.uri);
+        false
+    }
+
+    /// store the recorded fingerprints into file `path`
+    ///
+    /// the first line is the header, `<version> exact <len>` or
+    /// `<version> bloom <len> <size> <hashes>`, followed by fingerprints or words of bits in hex,
+    /// one per line
+    pub fn store(&self, path: &str) -> io::Result<()> {
+        let tmp = format!("{}.tmp", path);
+        let mut file = BufWriter::new(std::fs::File::create(&tmp)?);
+        match &self.seen {
+            Seen::Exact(set) => {
+                writeln!(file, "{} exact {}", VERSION, self.len)?;
+                for fp in set.iter() {
+                    writeln!(file, "{:x}", fp)?;
+                }
+            }
+            Seen::Bloom(bloom) => {
+                writeln!(
+                    file,
+                    "{} bloom {} {} {}",
+                    VERSION, self.len, bloom.size, bloom.hashes
+                )?;
+                for word in bloom.bits.iter() {
+                    writeln!(file, "{:x}", word)?;
+                }
+            }
+        }
+        file.flush()?;
+        drop(file);
+        std::fs::rename(&tmp, path)
+    }
+
+    /// load the fingerprints stored in file `path` into the filter
+    pub fn load(&mut self, path: &str) -> io::Result<()> {
+        let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+        let file = std::fs::File::open(path)?;
+        let mut lines = BufReader::new(file).lines();
+        let header = lines.next().ok_or_else(|| invalid("empty dupe file"))??;
+        let mut fields = header.split_whitespace().collect::<Vec<_>>();
+        // the files without version are fingerprinted by `DefaultHasher`
+        match fields.first().map(|version| version.parse::<u8>()) {
+            Some(Ok(VERSION)) => {
+                fields.remove(0);
+            }
+            _ => return Err(invalid("unsupported version of dupe file")),
+        }
+        let mut values = Vec::new();
+        for line in lines {
+            let line = line?;
+            let value = u64::from_str_radix(line.trim(), 16)
+                .map_err(|_| invalid("invalid fingerprint in dupe file"))?;
+            values.push(value);
+        }
+        match fields.as_slice() {
+            ["exact", _] => {
+                values.into_iter().for_each(|fp| {
+                    self.insert(fp);
+                });
+            }
+            ["bloom", len, size, hashes] => {
+                let len = len.parse().map_err(|_| invalid("invalid length"))?;
+                let size = size.parse::<u64>().map_err(|_| invalid("invalid size"))?;
+                let hashes = hashes.parse().map_err(|_| invalid("invalid hashes"))?;
+                if size == 0 || hashes == 0 {
+                    return Err(invalid("empty bloom filter"));
+                }
+                if values.len() as u64 != size.div_ceil(64) {
+                    return Err(invalid("bits of bloom filter mismatched"));
+                }
+                if self.mode == DupeMode::Exact {
+                    log::warn!("Fingerprints in bloom filter cannot be loaded in exact mode");
+                    return Ok(());
+                }
+                if !self.is_empty() {
+                    log::warn!("Fingerprints recorded before loading are discarded");
+                }
+                self.seen = Seen::Bloom(Bloom {
+                    bits: values,
+                    size,
+                    hashes,
+                });
+                self.len = len;
+            }
+            _ => return Err(invalid("invalid header of dupe file")),
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for DupeFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DupeFilter")
+            .field("is_on", &self.is_on)
+            .field("mode", &self.mode)
+            .field("len", &self.len)
+            .field("dropped", &self.dropped)
+            .finish()
+    }
+}
+
+#[test]
+fn test_dupe() {
+    let uri: Uri = "HTTP://Example.COM:80/a?b=2&a=1".parse().unwrap();
+    assert_eq!(canonical_uri(&uri), "http://example.com/a?a=1&b=2");
+
+    let task = |uri: &str| {
+        let mut task = Task::default();
+        task.inner.uri = uri.parse().unwrap();
+        task.metat.info.unique = true;
+        task
+    };
+    let mut arg = ArgDupe::new();
+    for mode in [DupeMode::Exact, DupeMode::Bloom].iter() {
+        arg.mode = *mode;
+        let mut filter = DupeFilter::new(&arg);
+        assert!(filter.check(&task("https://example.com/?a=1&b=2")));
+        assert!(!filter.check(&task("https://EXAMPLE.com:443/?b=2&a=1")));
+        assert!(filter.check(&task("https://example.com/page")));
+        let mut other = task("https://example.com/page");
+        other.metat.info.unique = false;
+        assert!(filter.check(&other));
+        assert_eq!(filter.len(), 2);
+        assert_eq!(filter.dropped, 1);
+    }
+
+    // custom fingerprint, migrated when switching mode
+    arg.mode = DupeMode::Exact;
+    let mut filter = DupeFilter::new(&arg);
+    filter.fingerprint = Some(Box::new(|task: &Task| task.inner.uri.path().len() as u64));
+    assert!(filter.check(&task("https://example.com/ab")));
+    assert!(!filter.check(&task("https://example.org/cd")));
+    arg.mode = DupeMode::Bloom;
+    filter.configure(&arg);
+    assert!(!filter.check(&task("https://example.org/ef")));
+    assert_eq!(filter.dropped, 2);
+
+    // stored and loaded
+    let path = std::env::temp_dir().join(format!("dyer-dupe-{}", std::process::id()));
+    let path = path.to_str().unwrap();
+    for mode in [DupeMode::Exact, DupeMode::Bloom].iter() {
+        arg.mode = *mode;
+        let mut filter = DupeFilter::new(&arg);
+        assert!(filter.check(&task("https://example.com/a")));
+        assert!(filter.check(&task("https://example.com/b")));
+        filter.store(path).unwrap();
+        let mut loaded = DupeFilter::new(&arg);
+        loaded.load(path).unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert!(!loaded.check(&task("https://example.com/b")));
+        assert!(loaded.check(&task("https://example.com/c")));
+    }
+    // fixed across Rust releases
+    assert_eq!(
+        fingerprint(&task("https://example.com/a"), &[]),
+        0x247c9dd9077ab72d
+    );
+    // file of other version refused
+    std::fs::write(path, "exact 1\n1f\n").unwrap();
+    assert!(DupeFilter::new(&arg).load(path).is_err());
+    // bloom filter without bits or hashes refused
+    std::fs::write(path, format!("{} bloom 0 0 1\n", VERSION)).unwrap();
+    assert!(DupeFilter::new(&arg).load(path).is_err());
+    std::fs::write(path, format!("{} bloom 0 64 0\n0\n", VERSION)).unwrap();
+    assert!(DupeFilter::new(&arg).load(path).is_err());
+    std::fs::remove_file(path).unwrap();
+}
@@ -3,36 +3,51 @@
 //! generating `Task`, preparation before opening actor, affairs before closing actor.  
 
 use crate::component::{body::Body, couple::Couple, Affix, Poly, Request, Response, Task};
+use crate::engine::actor::{Erased, Wrapper};
+use crate::engine::dupe::DupeFilter;
+use crate::engine::event::{DropReason, Event, Subscriber};
+use crate::engine::frontier::{DiskFrontier, Frontier, Loader, MemoryFrontier, Storer};
+use crate::engine::handle::{Handle, Status};
+use crate::engine::host::{host_key, Hosts};
+use crate::engine::limiter::RateLimiter;
+use crate::engine::pool::{AffixPool, Leave};
+use crate::engine::stats::{Stats, Summary};
 use crate::engine::Actor;
 use crate::engine::{appfut::AppFut, arg::ArgAffix, vault::Vault, ArgApp};
 use crate::plugin::Affixor;
 use crate::plugin::{MiddleWare, PipeLine};
-use crate::response::MetaResponse;
+use crate::response::{FailureKind, MetaResponse};
 use crate::utils;
 use crate::Parsed;
 use http::Extensions;
-use signal_hook::flag as signal_flag;
 use std::collections::HashMap;
 use std::error::Error;
 use std::iter::FromIterator;
-use std::{
-    collections::VecDeque,
-    sync::atomic::{AtomicUsize, Ordering},
-};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// maximal seconds the idle `App` sleeps before checking again
+const IDLE_MAX: f64 = 1.0;
+
+/// the session storer and loader shared by `App` and its default queue of `Task`
+type Session = Arc<Mutex<(Option<Box<Storer>>, Option<Box<Loader>>)>>;
 
 /// An abstraction and collection of data flow  
 pub struct App<E> {
-    /// a vector of `Task`, store them into directory if too many
-    /// in order to lower the memory
-    pub task: Vault<VecDeque<Task>>,
-    /// cached `Task`to be used  
-    pub task_tmp: Vault<Vec<Task>>,
-    /// a vector of `Affix`
-    pub affix: Vault<VecDeque<Affix>>,
-    /// a vector of `Request`
-    pub req: Vault<VecDeque<Request>>,
-    /// cached `Request`to be spawned  
-    pub req_tmp: Vault<Vec<Request>>,
+    /// a priority queue of `Task`, store them into directory if too many
+    /// in order to lower the memory, [DiskFrontier] under `data_dir/tasks/` keeping at most
+    /// `buf_task` ones in memory as default
+    pub task: Vault<Box<dyn Frontier<Task>>>,
+    /// cached `Task`to be used, [MemoryFrontier] as default
+    pub task_tmp: Vault<Box<dyn Frontier<Task>>>,
+    /// a priority queue of `Affix`, the idle ones of the pool
+    pub affix: Vault<MemoryFrontier<Affix>>,
+    /// health of the `Affix` in the pool
+    pub affix_pool: AffixPool,
+    /// a priority queue of `Request`, [MemoryFrontier] as default
+    pub req: Vault<Box<dyn Frontier<Request>>>,
+    /// cached `Request`to be spawned, [MemoryFrontier] as default
+    pub req_tmp: Vault<Box<dyn Frontier<Request>>>,
     /// a vector of `Response`
     pub res: Vault<Vec<Result<Response, MetaResponse>>>,
     /// collected entities
@@ -43,6 +58,24 @@ pub struct App<E> {
     pub(crate) fut_res: AppFut,
     /// future `Affix` with time stamp by which joined forcefully
     pub(crate) fut_affix: AppFut,
+    /// requests in flight of each host
+    pub(crate) hosts: Hosts,
+    /// token buckets of `rate.rps` and `host.rps`
+    pub(crate) limiter: RateLimiter,
+    /// drop the duplicate `Task`
+    pub(crate) dupe: DupeFilter,
+    /// counters of the data flow
+    pub stats: Stats,
+    /// control the `App` at runtime
+    pub(crate) handle: Handle,
+    /// handle the signals or not
+    pub(crate) signals: bool,
+    /// subscribers of `Event`
+    pub(crate) subscribers: Vec<Box<Subscriber>>,
+    /// actors added besides the one passed to `run`
+    pub(crate) actors: Vec<Box<dyn Erased<E> + Send>>,
+    /// index of the actor whose `Affixor` generates the next `Affix`
+    pub(crate) affix_turn: usize,
     /// Some argument to control the data flow
     pub args: ArgApp,
     /// couples of task and affix,
@@ -54,6 +87,8 @@ pub struct App<E> {
     /// called to load `Poly` at resuming session or load `Task` to execute
     /// NOTE that if `None` App Starts with new session
     pub session_loader: Option<Box<dyn Fn(&str) -> Poly + Send>>,
+    /// the session storer and loader, shared with the default queue of `Task`
+    pub(crate) session: Session,
     /// modify the body of [Task], [Affix] in [Couple]
     /// return the [Request]'s [Body]
     /// if not set, just simply concat the two of them
@@ -70,21 +105,41 @@ pub struct App<E> {
 impl<'a, E> App<E> {
     /// create an instance of `App`
     pub fn new() -> Self {
+        let args = ArgApp::new();
+        let handle = Handle::new();
+        let session = Session::default();
+        let task = DiskFrontier::new(
+            &format!("{}tasks/", args.data_dir),
+            args.buf_task,
+            shared_storer(&session),
+            shared_loader(&session),
+        );
         App {
-            task: Vault::new(VecDeque::new()),
-            task_tmp: Vault::new(Vec::new()),
-            affix: Vault::new(VecDeque::new()),
-            req: Vault::new(VecDeque::new()),
-            req_tmp: Vault::new(Vec::new()),
+            task: Vault::new(Box::new(task)),
+            task_tmp: Vault::new(Box::new(MemoryFrontier::new())),
+            affix: Vault::new(MemoryFrontier::new()),
+            affix_pool: AffixPool::new(),
+            req: Vault::new(Box::new(MemoryFrontier::new())),
+            req_tmp: Vault::new(Box::new(MemoryFrontier::new())),
             res: Vault::new(Vec::new()),
             entities: Vault::new(Vec::new()),
             errs: Vault::new(Vec::new()),
-            fut_res: AppFut::new(),
-            fut_affix: AppFut::new(),
+            fut_res: AppFut::new(handle.wake.clone()),
+            fut_affix: AppFut::new(handle.wake.clone()),
+            hosts: Hosts::new(),
+            limiter: RateLimiter::new(),
+            dupe: DupeFilter::new(&args.arg_dupe),
+            stats: Stats::new(),
+            handle,
+            signals: false,
+            subscribers: Vec::new(),
+            actors: Vec::new(),
+            affix_turn: 0,
             couple: Vault::new(HashMap::new()),
-            args: ArgApp::new(),
+            args,
             session_storer: None,
             session_loader: None,
+            session,
             body_modifier: None,
             exts_t_fn: None,
             exts_p_fn: None,
@@ -92,22 +147,95 @@ impl<'a, E> App<E> {
         }
     }
 
-    /// add an Actor
-    pub fn add_actor<A>(&mut self, spd: &dyn Actor<E, A>)
+    /// add an Actor driven along with the one passed to `run`,
+    /// sharing the clients, queues, middleware and pipeline
+    pub fn add_actor<T, A>(&mut self, spd: T)
     where
+        T: Actor<E, A> + Send + 'static,
         A: Affixor + Send + 'static,
     {
-        let _ = spd;
+        self.actors.push(Box::new(Wrapper::new(Box::new(spd))));
+    }
+
+    /// get a cloneable handle to stop, pause or resume the `App` at runtime
+    pub fn handle(&self) -> Handle {
+        self.handle.clone()
+    }
+
+    /// handle the signals or not, `false` as default
+    /// - SIGINT(Ctrl+c) and SIGTERM: exit gracefully, the same as [Handle::stop]
+    /// - SIGHUP: reload the config file
+    pub fn signals(&mut self, on: bool) {
+        self.signals = on;
+    }
+
+    /// get the summary of the data flow so far
+    pub fn summary(&self) -> Summary {
+        self.stats.summary()
     }
 
     /// set the Session Loader
     pub fn session_loader(&mut self, loader: Box<dyn Fn(&str) -> Poly + Send>) {
-        self.session_loader = Some(loader);
+        self.session.lock().unwrap().1 = Some(loader);
+        self.session_loader = Some(shared_loader(&self.session));
     }
 
     /// set the Session Storer
     pub fn session_storer(&mut self, storer: Box<dyn for<'b> Fn(Poly, &'b ()) -> &'b str + Send>) {
-        self.session_storer = Some(storer);
+        self.session.lock().unwrap().0 = Some(storer);
+        self.session_storer = Some(shared_storer(&self.session));
+    }
+
+    /// share the session storer and loader assigned to the fields directly
+    /// with the default queue of `Task`
+    fn share_session(&mut self) {
+        let mut session = self.session.lock().unwrap();
+        if session.0.is_none() && self.session_storer.is_some() {
+            session.0 = self.session_storer.take();
+            self.session_storer = Some(shared_storer(&self.session));
+        }
+        if session.1.is_none() && self.session_loader.is_some() {
+            session.1 = self.session_loader.take();
+            self.session_loader = Some(shared_loader(&self.session));
+        }
+    }
+
+    /// set the fingerprint of `Task` by which the duplicate ones are dropped,
+    /// if not set, computed from its method, canonical uri, selected headers and body
+    pub fn fingerprint(&mut self, f: Box<dyn Fn(&Task) -> u64 + Send>) {
+        self.dupe.fingerprint = Some(f);
+    }
+
+    /// drop the `Task` seen before among `tasks` whose `Info.unique` is `true`
+    pub fn dedup(&mut self, tasks: &mut Vec<Task>) {
+        let dupe = &mut self.dupe;
+        let mut dropped = Vec::new();
+        tasks.retain(|task| {
+            let keep = dupe.check(task);
+            if !keep {
+                dropped.push((task.metat.info.id, task.metat.info.marker.clone()));
+            }
+            keep
+        });
+        for (id, marker) in dropped {
+            self.stats.dropped(&marker, DropReason::Duplicate, 1);
+            self.emit(Event::Dropped {
+                id,
+                reason: DropReason::Duplicate,
+            });
+        }
+    }
+
+    /// add a subscriber of `Event` emitted at runtime
+    pub fn subscribe(&mut self, f: Box<Subscriber>) {
+        self.subscribers.push(f);
+    }
+
+    /// emit the `Event` to all subscribers
+    pub(crate) fn emit(&self, event: Event) {
+        for ff in self.subscribers.iter() {
+            ff(&event);
+        }
     }
 
     /// set the task extension handler
@@ -143,6 +271,89 @@ impl<'a, E> App<E> {
         self.body_modifier = Some(f);
     }
 
+    /// store the fingerprints of seen `Task` into `data_dir`
+    fn store_dupe(&self) {
+        let path = format!("{}dupe", self.args.data_dir);
+        if let Err(e) = self.dupe.store(&path) {
+            log::error!("Failed to store fingerprints into {}: {}", path, e);
+        }
+    }
+
+    /// store the queues, couples and fingerprints into `data_dir`,
+    /// only fingerprints are stored if session storer not provided,
+    /// the queue of `Task` is copied rather than taken, so it keeps running
+    fn backup(&mut self) {
+        self.store_dupe();
+        if self.session_storer.is_none() {
+            log::warn!("Session Storer Not Provided, Backup Skipped");
+            return;
+        }
+        log::info!("Backup History...");
+        let path = format!("{}affix", self.args.data_dir);
+        utils::stored(&path, &mut self.affix, self.session_storer.as_ref());
+        let path = format!("{}task", self.args.data_dir);
+        let mut tasks = Vec::with_capacity(self.task.as_ref().len());
+        self.task
+            .as_ref()
+            .visit(&mut |task| tasks.push(task.snapshot()));
+        utils::stored(&path, &mut Vault::new(tasks), self.session_storer.as_ref());
+        let path = format!("{}task_tmp", self.args.data_dir);
+        utils::stored(
+            &path,
+            &mut drained(&self.task_tmp),
+            self.session_storer.as_ref(),
+        );
+        let path = format!("{}request", self.args.data_dir);
+        utils::stored(&path, &mut drained(&self.req), self.session_storer.as_ref());
+        let path = format!("{}request_tmp", self.args.data_dir);
+        utils::stored(
+            &path,
+            &mut drained(&self.req_tmp),
+            self.session_storer.as_ref(),
+        );
+        let path = format!("{}couple", self.args.data_dir);
+        utils::stored(&path, &mut self.couple, self.session_storer.as_ref());
+        self.sync_args();
+        self.emit(Event::BackedUp);
+    }
+
+    /// join the futures in flight within `drain_timeout`, the unfinished ones are aborted
+    /// and their requests are rebuilt from couples to be stored
+    async fn drain(&mut self) {
+        let timeout = self.args.drain_timeout;
+        log::info!("Joining All Futures in {} seconds", timeout);
+        let aborted = self.fut_res.drain(timeout).await;
+        let mut reqs = Vec::new();
+        for id in aborted {
+            self.hosts.release(id);
+            if let Some(couple) = self.couple.as_ref().get(&id) {
+                reqs.push(Request::from_couple(
+                    couple,
+                    self.body_modifier.as_ref(),
+                    self.exts_t_fn.as_ref(),
+                    self.exts_p_fn.as_ref(),
+                ));
+            }
+        }
+        if !reqs.is_empty() {
+            log::info!("{} unfinished request(s) kept", reqs.len());
+            self.req.as_mut().extend(reqs);
+        }
+        self.fut_affix.drain(0.0).await;
+    }
+
+    /// keep the queues and filter in line with `ArgApp`
+    fn sync_args(&mut self) {
+        let aging = self.args.aging;
+        self.task.as_mut().set_aging(aging);
+        self.task_tmp.as_mut().set_aging(aging);
+        self.req.as_mut().set_aging(aging);
+        self.req_tmp.as_mut().set_aging(aging);
+        self.affix.as_mut().aging = aging;
+        self.dupe.configure(&self.args.arg_dupe);
+        self.hosts.configure(&self.args.arg_host);
+    }
+
     fn info(&mut self) {
         let mut vs = Vec::new();
         vs.push("Stats Overview:".to_string());
@@ -158,6 +369,10 @@ impl<'a, E> App<E> {
         if len_affix != 0 {
             vs.push(format!("{} Affix(s)", len_affix));
         }
+        let evicted = self.affix_pool.evicted;
+        if evicted != 0 {
+            vs.push(format!("{} Evicted Affix(s)", evicted));
+        }
         let len_req = self.req.as_ref().len();
         if len_req != 0 {
             vs.push(format!("{} Request(s)", len_req));
@@ -178,15 +393,24 @@ impl<'a, E> App<E> {
         if len_errs != 0 {
             vs.push(format!("{} Yield Error(s)", len_errs));
         }
-        let len_couple = self.couple.len();
+        let len_couple = self.couple.as_ref().len();
         if len_couple != 0 {
             vs.push(format!("{} Buffered Couple(s)", len_couple));
         }
-        let len_fut_res = self.fut_res.index.len();
+        if self.dupe.dropped != 0 {
+            vs.push(format!("{} Duplicate Task(s) Dropped", self.dupe.dropped));
+        }
+        if self.stats.depth_dropped != 0 {
+            vs.push(format!(
+                "{} Too Deep Task(s) Dropped",
+                self.stats.depth_dropped
+            ));
+        }
+        let len_fut_res = self.fut_res.len();
         if len_fut_res != 0 {
             vs.push(format!("{} Future Response(s)", len_fut_res));
         }
-        let len_fut_affix = self.fut_affix.index.len();
+        let len_fut_affix = self.fut_affix.len();
         if len_fut_affix != 0 {
             vs.push(format!("{} Future Affix(s)", len_fut_affix));
         }
@@ -197,25 +421,39 @@ impl<'a, E> App<E> {
         }
     }
 
+    /// take the `Affixor` of actors in turn
+    async fn next_affixor(
+        &mut self,
+        actors: &mut [&mut dyn Erased<E>],
+    ) -> Option<Box<dyn Affixor + Send>> {
+        let len = actors.len();
+        for i in 0..len {
+            let index = (self.affix_turn + i) % len;
+            if let Some(actor) = actors[index].entry_affix().await {
+                self.affix_turn = index + 1;
+                return Some(actor);
+            }
+        }
+        None
+    }
+
     /// to see whether to generate `Affix`
-    async fn update_affix<A>(&mut self, spd: &'a mut dyn Actor<E, A>)
-    where
-        A: Affixor + Send + 'static,
-    {
+    async fn update_affix(&mut self, actors: &mut [&mut dyn Erased<E>]) {
         log::trace!("Step into update_affix");
-        if spd.entry_affix().await.is_none() || !self.args.affix_on() {
+        if !self.args.affix_on() {
             return;
         }
         if let Some(ArgAffix {
             is_on: true,
             affix_min,
             affix_max,
+            ..
         }) = self.args.arg_affix
         {
             // affix customization is on
             let rd1 = (utils::now() * 3000.0) % 1.0;
             let affix_len = self.affix.as_ref().len()
-                + self.fut_affix.index.len()
+                + self.fut_affix.len()
                 + self.req.as_ref().len()
                 + self.req_tmp.as_ref().len();
             let less = affix_len <= affix_min;
@@ -227,15 +465,22 @@ impl<'a, E> App<E> {
                 emer = true;
             }
             if (less || exceed) && fut_exceed || emer {
+                let mut actor = match self.next_affixor(actors).await {
+                    Some(actor) => actor,
+                    None => return,
+                };
                 let now = utils::now();
                 log::info!("{} requests spawned for Affix", 3);
-                let mut actor = spd.entry_affix().await.unwrap();
                 if let Some(mut req) = actor.invoke().await {
                     // use network-based way to generate affix
-                    let mut affix = self.affix.clone();
+                    let affix = self.affix.clone();
                     let hash = req.metar.info.id;
                     actor.after_invoke().await;
-                    let handle = tokio::spawn(async move {
+                    let timeout = req.metar.info.timeout.or(self.args.timeout);
+                    req.metar.info.timeout = timeout;
+                    req.metar.info.max_body = req.metar.info.max_body.or(self.args.max_body);
+                    let deadline = now + self.args.join_gap.max(timeout.total.unwrap_or(0.0));
+                    self.fut_affix.spawn(hash, deadline, async move {
                         //let handle = self .pool .spawn_with_handle(async move {
                         // generate one `Affix`
                         // construct a new reqeust
@@ -245,20 +490,19 @@ impl<'a, E> App<E> {
                         actor.before_parse(Some(&mut res)).await;
                         if let Some(item) = actor.parse(Some(res)).await {
                             log::info!("Affix {}  generated", item.metap.info.id);
-                            affix.as_mut().push_back(item);
+                            affix.as_mut().push(item);
                             actor.after_parse().await;
                         } else {
                             log::debug!("Affix not generated",);
                         }
                     });
-                    self.fut_affix.insert(handle, hash, now);
                 } else {
                     // non network-way
                     actor.after_invoke().await;
                     actor.before_parse(None).await;
                     if let Some(item) = actor.parse(None).await {
                         log::info!("Affix {}  generated", item.metap.info.id);
-                        self.affix.as_mut().push_back(item);
+                        self.affix.as_mut().push(item);
                         actor.after_parse().await;
                     } else {
                         log::debug!("Affix not generated",);
@@ -274,6 +518,7 @@ impl<'a, E> App<E> {
     /// drive and consume extracted Entity into `PipeLine`
     async fn plineout<'b, C>(&mut self, pipeline: &PipeLine<'b, E, C>) {
         log::trace!("Step into plineout");
+        let mut dumped = (0, 0);
         if self.errs.as_ref().len() > self.args.round_errs {
             log::info!("Pipeline put out yield_parse_err");
             let mut yerrs = Vec::new();
@@ -283,6 +528,7 @@ impl<'a, E> App<E> {
                 }
             });
             //std::mem::swap(&mut yerrs, &mut *self.errs);
+            dumped.1 = yerrs.len();
             if let Some(ff) = pipeline.yerr() {
                 ff(yerrs, self).await;
             }
@@ -296,10 +542,17 @@ impl<'a, E> App<E> {
                     ens.push(e);
                 }
             });
+            dumped.0 = ens.len();
             if let Some(ff) = pipeline.entity() {
                 ff(ens, self).await;
             }
         }
+        if dumped != (0, 0) {
+            self.emit(Event::Dumped {
+                entities: dumped.0,
+                errors: dumped.1,
+            });
+        }
     }
 
     /// load and balance `Request`
@@ -309,17 +562,14 @@ impl<'a, E> App<E> {
         let len_req_tmp = self.req_tmp.as_ref().len();
         if len_req_tmp <= self.args.round_req_min {
             // cached request is not enough
-            let len_req = self.req.as_ref().len();
+            let len_req = self.req.as_ref().len().min(self.args.round_req_max);
             let mut requests = Vec::new();
 
             //  limit len_req and reqs that is availible by now
             for _ in 0..len_req {
-                let request = self.req.as_mut().pop_front().unwrap();
-                if request.metar.info.able <= now {
-                    requests.push(request);
-                } else {
-                    self.req.as_mut().push_front(request);
-                    break;
+                match self.req.as_mut().pop(now) {
+                    Some(request) => requests.push(request),
+                    None => break,
                 }
             }
             if let Some(ff) = middleware.req() {
@@ -345,20 +595,32 @@ impl<'a, E> App<E> {
         if affix_on && len.min(len_affix) > 0 {
             let len = len.min(len_affix);
             log::debug!("Creating {} request", len);
-            for _ in 0..len {
+            let able = |next: Option<f64>, now: f64| matches!(next, Some(able) if able <= now);
+            // affixes no available `Task` accepts for now
+            let mut held = Vec::new();
+            while reqs.len() < len {
                 let now = utils::now();
-                let affix = self.affix.as_mut().pop_back().unwrap();
-                if affix.metap.info.able > now {
-                    // not available right now
-                    self.affix.as_mut().push_front(affix);
-                    break;
-                }
-                let task = self.task.as_mut().pop_back().unwrap();
-                if task.metat.info.able > now {
-                    // not available right now
-                    self.task.as_mut().push_front(task);
+                if !able(self.task.as_ref().next_able(), now)
+                    || !able(self.affix.as_ref().next_able(), now)
+                {
+                    // not available right now, woken up by the next `able`
                     break;
                 }
+                let affix = self.affix.as_mut().pop(now).unwrap();
+                // the `Task` not accepting it is held back as it was,
+                // at most `round_req_max` checked
+                let task = self.task.as_mut().pop_with(
+                    now,
+                    self.args.round_req_max,
+                    &mut |task: &Task| task.accepts(&affix),
+                );
+                let task = match task {
+                    Some(task) => task,
+                    None => {
+                        held.push(affix);
+                        continue;
+                    }
+                };
                 let couple = Couple::new(task, Some(affix));
                 let req = Request::from_couple(
                     &couple,
@@ -366,20 +628,23 @@ impl<'a, E> App<E> {
                     self.exts_t_fn.as_ref(),
                     self.exts_p_fn.as_ref(),
                 );
-                self.couple.insert(couple.id, couple);
+                self.couple.as_mut().insert(couple.id, couple);
                 log::debug!("Created Request: {:?}", req);
                 reqs.push(req);
             }
+            if !held.is_empty() {
+                log::debug!("{} Affix(s) not accepted by any Task for now", held.len());
+                self.affix.as_mut().extend(held);
+            }
         } else if !affix_on && len > 0 {
             log::debug!("Creating {} request", len);
             for _ in 0..len {
                 let now = utils::now();
-                let task = self.task.as_mut().pop_back().unwrap();
-                if task.metat.info.able > now {
+                let task = match self.task.as_mut().pop(now) {
+                    Some(task) => task,
                     // not available right now
-                    self.task.as_mut().push_front(task);
-                    break;
-                }
+                    None => break,
+                };
                 let couple = Couple::new(task, None);
                 let req = Request::from_couple(
                     &couple,
@@ -389,7 +654,7 @@ impl<'a, E> App<E> {
                 );
                 log::trace!("Created request: {:?}", req);
                 reqs.push(req);
-                self.couple.insert(couple.id, couple);
+                self.couple.as_mut().insert(couple.id, couple);
             }
         }
         self.req.as_mut().extend(reqs);
@@ -398,26 +663,73 @@ impl<'a, E> App<E> {
     /// spawn polling `Request` as `tokio::task` and executing asynchronously,
     async fn spawn_task(&mut self) {
         log::trace!("Step into spawn_task");
-        if self.fut_res.index.len() > self.args.spawn_task_max {
+        if self.fut_res.len() > self.args.spawn_task_max {
             if self.args.rate.as_mut().update() {
                 log::warn!("Enough Future Response, spawn no task.");
             }
             return;
         }
         log::trace!("Take request out to be executed.");
-        self.req_tmp
-            .as_mut()
-            .sort_by(|a, b| a.info().rank.cmp(&b.info().rank));
-        let len = self.args.round_req.min(self.req_tmp.as_ref().len());
-        let len_load = self.args.rate.as_mut().get_len(None).min(len);
+        let len = self
+            .args
+            .round_req
+            .min(self.req_tmp.as_ref().len())
+            .min(self.args.arg_budget.requests_left(&self.stats));
+        let (rps, burst) = {
+            let rate = self.args.rate.as_ref();
+            (rate.rps, rate.burst)
+        };
+        // the token bucket replaces the load model once `rate.rps` is set
+        let len_load = if rps > 0.0 {
+            len
+        } else {
+            self.args.rate.as_mut().get_len(None).min(len)
+        };
         for _ in 0..len_load {
+            if !self.limiter.ready(rps, burst) {
+                log::debug!("Rate limit reached, spawn no more request for now");
+                break;
+            }
             let now = utils::now();
-            let mut req = self.req_tmp.as_mut().pop().unwrap();
+            // skip the requests whose host is busy for now, at most `round_req_max` checked
+            let hosts = &mut self.hosts;
+            let limiter = &mut self.limiter;
+            let arg_host = &self.args.arg_host;
+            let popped = self.req_tmp.as_mut().pop_with(
+                now,
+                self.args.round_req_max,
+                &mut |req: &Request| {
+                    let host = host_key(&req.inner.uri, arg_host.by_domain);
+                    limiter.host_ready(&host, arg_host)
+                        && hosts.acquire(req.metar.info.id, host, arg_host, now)
+                },
+            );
+            let mut req = match popped {
+                Some(req) => req,
+                None => {
+                    log::debug!("No request available for idle host");
+                    break;
+                }
+            };
+            self.limiter
+                .take(&host_key(&req.inner.uri, arg_host.by_domain), arg_host);
             let hash = req.metar.info.id;
-            let mut app_arg = self.args.rate.clone();
-            let mut app_res = self.res.clone();
+            let timeout = req.metar.info.timeout.or(self.args.timeout);
+            req.metar.info.timeout = timeout;
+            req.metar.info.max_body = req.metar.info.max_body.or(self.args.max_body);
+            self.stats.request(&req.metar.info.marker);
+            if !self.subscribers.is_empty() {
+                self.emit(Event::Spawned {
+                    id: hash,
+                    uri: req.inner.uri.clone(),
+                });
+            }
+            let app_arg = self.args.rate.clone();
+            let app_res = self.res.clone();
             //let mut couple = self.couple.clone();
-            let handle = tokio::spawn(async move {
+            // `join_gap` is the last resort, never cuts the total timeout short
+            let deadline = now + self.args.join_gap.max(timeout.total.unwrap_or(0.0));
+            self.fut_res.spawn(hash, deadline, async move {
                 //let handle = self .pool .spawn_with_handle(async move {
                 log::info!("Crawling requests: {} ", &req.inner.uri);
                 let client = req.get_client();
@@ -432,7 +744,6 @@ impl<'a, E> App<E> {
                     }
                 }
             });
-            self.fut_res.insert(handle, hash, now);
         }
     }
 
@@ -455,22 +766,63 @@ impl<'a, E> App<E> {
         let mut yerr = Vec::new();
         let mut ens = Vec::new();
         let mut errs = Vec::new();
-        let mut hashes = Vec::new();
 
         let len = self.res.as_ref().len().min(round);
         for _ in 0..len {
-            match self.res.as_mut().pop().unwrap() {
+            let res = self.res.as_mut().pop().unwrap();
+            match res {
                 Ok(item) => {
                     let status = item.status().as_u16();
                     let id = item.metas.info.id;
+                    let failed = status == 429 || status >= 500;
+                    let arg_host = &self.args.arg_host;
+                    self.hosts
+                        .observe(id, item.metas.info.gap, failed, arg_host);
+                    self.hosts.release(id);
+                    self.stats.response(
+                        &item.metas.info.marker,
+                        status,
+                        item.body.len(),
+                        item.metas.info.gap,
+                    );
+                    self.emit(Event::Received {
+                        id,
+                        status,
+                        gap: item.metas.info.gap,
+                    });
+                    self.observe_affix(id, Some(status));
                     if status >= 200 && status < 300 {
-                        self.couple.remove(&id);
+                        let couple = self.couple.as_mut().remove(&id);
+                        if let Some(couple) = couple {
+                            if let Some(affix) = couple.affix {
+                                self.release_affix(affix);
+                            }
+                            // put back as the couple removed, whatever happens to the response
+                            if let Some(task) = couple.task.recur(utils::now()) {
+                                log::debug!("Task {} runs again at {}", id, task.metat.info.able);
+                                self.task.as_mut().push(task);
+                            }
+                        }
                         v.push(item);
                         continue;
                     }
                     errs.push(Ok(item));
                 }
                 Err(meta) => {
+                    let arg_host = &self.args.arg_host;
+                    self.hosts
+                        .observe(meta.info.id, meta.info.gap, true, arg_host);
+                    self.hosts.release(meta.info.id);
+                    self.observe_affix(meta.info.id, None);
+                    let kind = meta.failure.unwrap_or(FailureKind::Other);
+                    self.stats.failure(&meta.info.marker, kind, 1);
+                    if !self.subscribers.is_empty() {
+                        self.emit(Event::Failed {
+                            id: meta.info.id,
+                            uri: meta.info.from.clone(),
+                            kind,
+                        });
+                    }
                     errs.push(Err(meta));
                 }
             }
@@ -485,19 +837,49 @@ impl<'a, E> App<E> {
                 ff(&mut v, self).await;
             }
         }
+        let referer = self.args.referer;
+        // the yielded `Task` and `Affix` cool down for `nap` seconds
+        let able = utils::now() + self.args.nap;
         while let Some(res) = v.pop() {
-            let (prs, hash) = self.parse(res).await;
+            let depth = res.metas.info.depth + 1;
+            let parent_uri = res.metas.info.from.clone();
+            let marker = res.metas.info.marker.clone();
+            let (mut prs, hash) = self.parse(res).await;
             log::trace!("response parsed: {}", hash);
-            hashes.push(hash);
-            tsks.extend(prs.task);
-            pfiles.extend(prs.affix);
+            self.emit(Event::Parsed {
+                id: hash,
+                tasks: prs.task.len(),
+                affixs: prs.affix.len(),
+                requests: prs.req.len(),
+                entities: prs.entities.len(),
+                errors: prs.errs.len(),
+            });
+            self.stats.entities(&marker, prs.entities.len());
+            for req in prs.req.iter_mut() {
+                if req.metar.info.marker.is_empty() {
+                    req.metar.info.marker = marker.clone();
+                }
+            }
+            tsks.extend(prs.task.into_iter().map(|mut task| {
+                let info = &mut task.metat.info;
+                if info.marker.is_empty() {
+                    info.marker = marker.clone();
+                }
+                info.depth = depth;
+                info.parent = hash;
+                info.parent_uri = Some(parent_uri.clone());
+                info.referer |= referer;
+                info.able = info.able.max(able);
+                task
+            }));
+            pfiles.extend(prs.affix.into_iter().map(|mut affix| {
+                affix.metap.info.able = affix.metap.info.able.max(able);
+                affix
+            }));
             reqs.extend(prs.req);
             yerr.extend(prs.errs);
             ens.extend(prs.entities);
         }
-        if !hashes.is_empty() {
-            self.fut_res.direct_join(hashes).await;
-        }
         if !reqs.is_empty() {
             if let Some(ff) = mware.req() {
                 ff(&mut reqs, self).await;
@@ -514,6 +896,28 @@ impl<'a, E> App<E> {
             if let Some(ff) = mware.task() {
                 ff(&mut tsks, self).await;
             }
+            let max_depth = self.args.max_depth;
+            if max_depth > 0 {
+                let mut dropped = Vec::new();
+                tsks.retain(|task| {
+                    let keep = task.metat.info.depth <= max_depth;
+                    if !keep {
+                        dropped.push((task.metat.info.id, task.metat.info.marker.clone()));
+                    }
+                    keep
+                });
+                if !dropped.is_empty() {
+                    log::debug!("Drop {} task(s) deeper than {}", dropped.len(), max_depth);
+                }
+                for (id, marker) in dropped {
+                    self.stats.dropped(&marker, DropReason::Depth, 1);
+                    self.emit(Event::Dropped {
+                        id,
+                        reason: DropReason::Depth,
+                    });
+                }
+            }
+            self.dedup(&mut tsks);
             self.task_tmp.as_mut().extend(tsks);
         }
         if !ens.is_empty() {
@@ -527,87 +931,83 @@ impl<'a, E> App<E> {
         }
     }
 
-    ///join spawned task, once it exceed the timing `join_gap`, then forcefully join it
+    /// record the outcome of the `Request` of `id` on the health of its `Affix`,
+    /// `status` is `None` if it failed without `Response`
+    fn observe_affix(&mut self, id: u64, status: Option<u16>) {
+        let affix = match self.couple.as_ref().get(&id).and_then(|c| c.affix.as_ref()) {
+            Some(affix) => affix.metap.info.id,
+            None => return,
+        };
+        let default = ArgAffix::new();
+        let arg = self.args.arg_affix.as_ref().unwrap_or(&default);
+        self.affix_pool.observe(affix, status, arg);
+    }
+
+    /// put the `Affix` of a done `Request` back into the pool, unless it is worn out
+    pub(crate) fn release_affix(&mut self, mut affix: Affix) {
+        let default = ArgAffix::new();
+        let arg = self.args.arg_affix.as_ref().unwrap_or(&default);
+        let id = affix.metap.info.id;
+        let now = utils::now();
+        match self.affix_pool.release(&mut affix, arg, self.args.nap, now) {
+            None => self.affix.as_mut().push(affix),
+            Some(Leave::Retired) => {
+                log::debug!("Affix {} retired after {} uses", id, affix.metap.info.used)
+            }
+            Some(Leave::Evicted) => {
+                log::warn!("Affix {} evicted for failures or bans", id);
+                self.emit(Event::Evicted { id });
+            }
+        }
+    }
+
+    /// cancel the spawned tasks unfinished by their deadlines, `join_gap` after spawned,
+    /// the cancelled requests are failed through the err middleware
     async fn watch(&mut self) {
         log::trace!("Step into watch");
-        let threshold_tokio_task = self.args.join_gap;
+        let now = utils::now();
         let capacity = self.args.round_req;
-        if !self.fut_res.index.is_empty() {
-            self.fut_res.cancell(threshold_tokio_task, capacity);
-            //self.fut_res.all(threshold_tokio_task, capacity).await;
-        }
-        if !self.fut_affix.index.is_empty() {
-            self.fut_affix.cancell(threshold_tokio_task, capacity);
-            //self.fut_affix.all(threshold_tokio_task, capacity).await;
-        }
-    }
-
-    /// load cached `Task` from caced directory
-    fn buf_task(&self) -> Vec<usize> {
-        log::trace!("Step into buf_task");
-        let path = format!("{}/tasks/", self.args.data_dir);
-        let mut file_indexs: Vec<usize> = Vec::new();
-        if let Ok(items) = std::fs::read_dir(path) {
-            items.for_each(|name| {
-                let index = name
-                    .unwrap()
-                    .file_name()
-                    .to_str()
-                    .unwrap()
-                    .parse::<usize>()
-                    .unwrap();
-                file_indexs.push(index);
-            });
+        if !self.fut_res.is_empty() {
+            let ids = self.fut_res.cancel(now, capacity);
+            for id in ids {
+                // rebuilt from the couple and failed through the normal path
+                let failed = self.couple.as_ref().get(&id).map(|couple| {
+                    let req = Request::from_couple(
+                        couple,
+                        self.body_modifier.as_ref(),
+                        self.exts_t_fn.as_ref(),
+                        self.exts_p_fn.as_ref(),
+                    );
+                    let mut meta = MetaResponse::from(req.metar);
+                    meta.failure = Some(FailureKind::Cancelled);
+                    meta
+                });
+                match failed {
+                    Some(meta) => self.res.as_mut().push(Err(meta)),
+                    None => {
+                        self.stats.failure("", FailureKind::Cancelled, 1);
+                        self.hosts.release(id);
+                    }
+                }
+                self.emit(Event::Cancelled { id });
+            }
+        }
+        if !self.fut_affix.is_empty() {
+            self.fut_affix.cancel(now, capacity);
         }
-        file_indexs
     }
 
-    /// update task in App
+    /// move the `Task` buffered from `parser`s into the queue once the queue runs out or the
+    /// buffer exceeds `buf_task`, spilling them or not is up to the [Frontier] of `task`
     fn update_task(&mut self) {
         log::trace!("Step into update_task");
-        let path = format!("{}/tasks/", self.args.data_dir);
-        if self.task.as_ref().is_empty() {
-            let file_indexs: Vec<usize> = self.buf_task();
-            if !file_indexs.is_empty() {
-                let file = format!("{}{}", path, file_indexs[0]);
-                log::warn!("Remove used task in {}", file);
-                std::fs::remove_file(&file).unwrap();
-            }
-            if file_indexs.len() <= 1 {
-                let mut task_tmp = Vec::new();
-                let mut tmp = self.task_tmp.as_mut();
-                for _ in 0..tmp.len() {
-                    let tsk = tmp.pop().unwrap();
-                    task_tmp.push(tsk);
-                }
-                if !task_tmp.is_empty() {
-                    log::debug!(
-                        "No task buffer file found. Load {} tasks from task_tmp",
-                        task_tmp.len()
-                    );
-                }
-                self.task.as_mut().extend(task_tmp);
-            } else if file_indexs.len() >= 2 {
-                let file_new = format!("{}tasks/{}", self.args.data_dir, file_indexs[1]);
-                let tsks = utils::load(&file_new, self.session_loader.as_ref());
-                log::info!("Load {} new task in {}", tsks.len(), file_new);
-                self.task.as_mut().extend(tsks);
-            }
-        }
-        if self.task_tmp.as_ref().len() >= self.args.buf_task {
-            log::debug!(
-                "Buffered Task Pipeline Dumping: {}",
-                self.task_tmp.as_ref().len()
-            );
-            let files = self.buf_task();
-            let file_name = format!(
-                "{}tasks/{}",
-                self.args.data_dir,
-                1 + files.last().unwrap_or(&0)
-            );
-            utils::stored(&file_name, &mut self.task_tmp, self.session_storer.as_ref());
-            self.task_tmp.as_mut().clear();
+        let len = self.task_tmp.as_ref().len();
+        if len == 0 || (!self.task.as_ref().is_empty() && len < self.args.buf_task) {
+            return;
         }
+        log::debug!("Load {} tasks from task_tmp", len);
+        let tasks = self.task_tmp.as_mut().drain();
+        self.task.as_mut().extend(tasks);
     }
 
     /// check all necessary exit conditions
@@ -616,23 +1016,23 @@ impl<'a, E> App<E> {
             && self.req_tmp.as_ref().is_empty()
             && self.task.as_ref().is_empty()
             && self.task_tmp.as_ref().is_empty()
-            && self.fut_res.index.is_empty()
+            && self.fut_res.is_empty()
             && self.res.as_ref().is_empty()
     }
 
     /// preparation before closing `Dyer`
-    async fn close<'b, C, A>(
-        &'a mut self,
-        spd: &'a mut dyn Actor<E, A>,
-        middleware: &'a MiddleWare<'b, E>,
-        pipeline: &'a PipeLine<'b, E, C>,
-    ) where
-        A: Affixor + Send + 'static,
-    {
+    async fn close<'b, C>(
+        &mut self,
+        actors: &mut [&mut dyn Erased<E>],
+        middleware: &MiddleWare<'b, E>,
+        pipeline: &PipeLine<'b, E, C>,
+    ) {
         log::trace!("Step into close");
         self.info();
-        if let Some(mut actor) = spd.entry_affix().await {
-            actor.close().await;
+        for spd in actors.iter_mut() {
+            if let Some(mut actor) = spd.entry_affix().await {
+                actor.close().await;
+            }
         }
         self.parse_all(middleware).await;
         log::info!("Pipeline Data Dumping");
@@ -660,35 +1060,135 @@ impl<'a, E> App<E> {
         log::info!("Clean the App");
     }
 
-    /// drive `Dyer` into running.
+    /// lengths of the queues and number of spawned requests,
+    /// unchanged in a round means nothing is done
+    fn footprint(&self) -> [usize; 11] {
+        [
+            self.task.as_ref().len(),
+            self.task_tmp.as_ref().len(),
+            self.affix.as_ref().len(),
+            self.req.as_ref().len(),
+            self.req_tmp.as_ref().len(),
+            self.res.as_ref().len(),
+            self.entities.as_ref().len(),
+            self.errs.as_ref().len(),
+            self.fut_res.len(),
+            self.fut_affix.len(),
+            self.stats.requests,
+        ]
+    }
+
+    /// the earliest time stamp after `now` by which something is scheduled,
+    /// at most `IDLE_MAX` seconds later
+    fn next_wakeup(&self, now: f64, paused: bool) -> f64 {
+        let rate = self.args.rate.as_ref();
+        let mut stamps = vec![now + IDLE_MAX, rate.anchor];
+        stamps.extend(self.args.arg_budget.deadline(&self.stats));
+        if !paused {
+            stamps.extend(self.task.as_ref().next_able());
+            stamps.extend(self.req.as_ref().next_able());
+            if self.args.affix_on() {
+                stamps.extend(self.affix.as_ref().next_able());
+            }
+            if !self.req_tmp.as_ref().is_empty() {
+                stamps.extend(self.req_tmp.as_ref().next_able());
+                if rate.rps > 0.0 {
+                    stamps.extend(self.limiter.next_able());
+                } else {
+                    stamps.push(rate.next_able(now));
+                }
+                stamps.extend(self.hosts.next_free(&self.args.arg_host, now));
+                stamps.extend(self.limiter.next_free());
+            }
+        }
+        stamps.extend(self.fut_res.next_deadline());
+        stamps.extend(self.fut_affix.next_deadline());
+        stamps
+            .into_iter()
+            .filter(|&stamp| stamp > now)
+            .fold(now + IDLE_MAX, f64::min)
+    }
+
+    /// sleep until woken up by arrived `Response` or `Affix`, `Handle` or signals,
+    /// or something scheduled
+    async fn idle(&self, paused: bool) {
+        let now = utils::now();
+        let wakeup = self.next_wakeup(now, paused);
+        let duration = std::time::Duration::from_secs_f64(wakeup - now);
+        log::trace!("Idle for {:.3} seconds", wakeup - now);
+        tokio::select! {
+            _ = self.handle.wake.notified() => {}
+            _ = tokio::time::sleep(duration) => {}
+        }
+    }
+
+    /// call `close_actor` of each actor
+    async fn close_actors(&mut self, actors: &mut [&mut dyn Erased<E>]) {
+        for spd in actors.iter_mut() {
+            spd.close_actor(self).await;
+            log::info!("Actor {} Closed", spd.marker());
+        }
+    }
+
+    /// drive `Dyer` into running, along with the actors added by `add_actor`.
     pub async fn run<'b, C, A>(
         &'a mut self,
         spd: &'a mut dyn Actor<E, A>,
         middleware: &'a MiddleWare<'b, E>,
         pipeline: &'a PipeLine<'b, E, C>,
-    ) -> Result<(), Box<dyn Error>>
+    ) -> Result<Summary, Box<dyn Error>>
     where
         A: Affixor + Send + 'static,
     {
+        self.stats.started = utils::now();
+
         // signal handling initial
-        let term = std::sync::Arc::new(AtomicUsize::new(0));
-        const SIGINT: usize = signal_hook::SIGINT as usize;
-        signal_flag::register_usize(signal_hook::SIGINT, term.clone(), SIGINT).unwrap();
+        // reload the config file on SIGHUP
+        let reload = std::sync::Arc::new(AtomicBool::new(false));
+        let listener = if self.signals {
+            Some(self.handle.listen(reload.clone())?)
+        } else {
+            None
+        };
+        // a paused `App` stays paused
+        if !self.handle.swap(Status::Ready, Status::Running) {
+            self.handle.swap(Status::Stopped, Status::Running);
+        }
+
+        self.sync_args();
+        self.share_session();
+        self.emit(Event::Started);
+
+        // the added actors are put back when finished
+        let mut spd = Wrapper::new(spd);
+        let mut added = std::mem::take(&mut self.actors);
+        let mut actors: Vec<&mut dyn Erased<E>> = vec![&mut spd];
+        actors.extend(
+            added
+                .iter_mut()
+                .map(|spd| spd.as_mut() as &mut dyn Erased<E>),
+        );
+        let actors = actors.as_mut_slice();
 
         // user defined preparation when open actor
-        spd.open_actor(self).await;
-        if let Some(mut actor) = spd.entry_affix().await {
-            actor.init().await;
+        for spd in actors.iter_mut() {
+            spd.open_actor(self).await;
+            if let Some(mut actor) = spd.entry_affix().await {
+                actor.init().await;
+            }
         }
 
         //skip the history and start new fields to staart with, some Affix required
         if self.args.skip {
             log::info!("New Session Started");
-            let mut tasks = spd.entry_task().await.unwrap();
-            if let Some(ff) = middleware.task() {
-                ff(&mut tasks, self).await;
+            for spd in actors.iter_mut() {
+                let mut tasks = spd.entry_task().await.unwrap();
+                if let Some(ff) = middleware.task() {
+                    ff(&mut tasks, self).await;
+                }
+                self.dedup(&mut tasks);
+                self.task.as_mut().extend(tasks);
             }
-            self.task.as_mut().extend(tasks);
             self.info();
         } else {
             log::info!("Resuming The Session");
@@ -700,111 +1200,230 @@ impl<'a, E> App<E> {
             let affix: Vec<Affix> = utils::load(&path, self.session_loader.as_ref());
             let path = format!("{}couple", self.args.data_dir);
             let couples: Vec<(u64, Couple)> = utils::load(&path, self.session_loader.as_ref());
-            let files = self.buf_task();
-            let file = format!("{}/{}", self.args.data_dir, files[0]);
-            let task: Vec<Task> = utils::load(&file, self.session_loader.as_ref());
-            log::info!("{} loaded {} Task.", file, task.len());
-            self.task
-                .append(&mut task.into_iter().collect::<VecDeque<_>>());
-            self.req
-                .append(&mut reqs.into_iter().collect::<VecDeque<_>>());
+            let path = format!("{}task_tmp", self.args.data_dir);
+            let task_tmp: Vec<Task> = utils::load(&path, self.session_loader.as_ref());
+            // queued ones, including the recurring ones waiting for the next run
+            let path = format!("{}task", self.args.data_dir);
+            let queued: Vec<Task> = utils::load(&path, self.session_loader.as_ref());
+            let path = format!("{}dupe", self.args.data_dir);
+            match self.dupe.load(&path) {
+                Ok(_) => log::info!("{} loaded {} fingerprints.", path, self.dupe.len()),
+                Err(e) => log::warn!("Failed to load fingerprints from {}: {}", path, e),
+            }
+            self.task.as_mut().extend(queued);
+            self.task_tmp.as_mut().extend(task_tmp);
+            self.req.as_mut().extend(reqs);
             self.couple
                 .replace(HashMap::<u64, Couple>::from_iter(couples));
-            *self.req_tmp = req_tmp;
-            self.affix
-                .append(&mut affix.into_iter().collect::<VecDeque<_>>());
+            self.req_tmp.as_mut().extend(req_tmp);
+            self.affix.as_mut().extend(affix);
             log::info!("History Files Loaded");
             self.info();
         }
 
         loop {
-            match term.load(Ordering::Relaxed) {
-                SIGINT => {
-                    // receive the Ctrl+c signal
-                    // by default request task affix
-                    // and result yield err are going to stroed into file
-                    log::info!("Receive Ctrl+c Signal, Preparing Exit ...");
-
-                    //finish remaining futures
-                    log::info!("Joining All Futures");
-                    let capacity = self.args.round_req;
-                    while !self.fut_res.data.is_empty() {
-                        self.fut_res.all(9999999.0, capacity).await;
-                    }
+            // pick up the finished futures
+            self.fut_res.reap();
+            self.fut_affix.reap();
 
-                    // dispath them
-                    log::info!("Closing Actor ...");
-                    self.close(spd, middleware, &pipeline).await;
-                    spd.close_actor(self).await;
-                    log::info!("All Work Is Done, Exiting ...");
-                    break;
+            // stop once a budget is exhausted
+            if self.stats.budget.is_none() {
+                let budget = self.args.arg_budget.exhausted(&self.stats, utils::now());
+                if let Some(budget) = budget {
+                    log::warn!("Budget of {} Exhausted, Stopping ...", budget);
+                    self.stats.budget = Some(budget);
+                    self.emit(Event::Exhausted { budget });
+                    self.handle.stop();
                 }
+            }
 
-                0 => {
-                    // if all task request and other things are done the quit
-                    if self.exit() {
-                        log::info!("Closing Actor ...");
-                        self.close(spd, middleware, &pipeline).await;
-                        spd.close_actor(self).await;
-                        log::info!("All Work Is Done, Exiting ...");
-                        break;
-                    }
+            let status = self.handle.status();
+            if status == Status::Stopping {
+                // receive the Ctrl+c or terminate signal or stopped by handle
+                // by default request task affix
+                // and result yield err are going to stroed into file
+                log::info!("Stopping, Preparing Exit ...");
+                self.emit(Event::Stopping);
 
-                    // before we update request check affix first
-                    self.update_affix(spd).await;
+                //finish remaining futures
+                self.drain().await;
 
-                    // consume valid request in cbase_reqs_tmp
-                    // if not enough take them from self.req
-                    self.update_req(middleware).await;
+                // dispath them
+                log::info!("Closing Actor ...");
+                self.close(actors, middleware, &pipeline).await;
+                self.backup();
+                self.close_actors(actors).await;
+                self.emit(Event::Closed);
+                log::info!("All Work Is Done, Exiting ...");
+                break;
+            }
+            // no request spawned while paused, keep the queues, the in-flight ones are
+            // still reaped and cancelled by deadline
+            let paused = status == Status::Paused;
 
-                    //take req out to finish
-                    self.spawn_task().await;
+            // if all task request and other things are done the quit
+            if !paused && self.exit() {
+                log::info!("Closing Actor ...");
+                self.close(actors, middleware, &pipeline).await;
+                self.close_actors(actors).await;
+                self.emit(Event::Closed);
+                log::info!("All Work Is Done, Exiting ...");
+                break;
+            }
 
-                    //pipeline put out yield_parse_err and Entity
-                    self.plineout(&pipeline).await;
+            let footprint = self.footprint();
 
-                    // if task is running out, load them from nex buf_task
-                    self.update_task();
+            if !paused {
+                // before we update request check affix first
+                self.update_affix(actors).await;
 
-                    // construct request
-                    self.gen_req();
+                // consume valid request in cbase_reqs_tmp
+                // if not enough take them from self.req
+                self.update_req(middleware).await;
 
-                    //join the older jobs
-                    self.watch().await;
+                //take req out to finish
+                self.spawn_task().await;
+            }
 
-                    self.parse_all(middleware).await;
+            //pipeline put out yield_parse_err and Entity
+            self.plineout(&pipeline).await;
 
-                    // update Rate
-                    let updated = self.args.rate.as_mut().update();
+            if !paused {
+                // if task is running out, load them from task_tmp
+                self.update_task();
 
-                    // update config file in each interval
-                    if updated {
-                        let data_dir = self.args.data_dir.clone();
-                        self.args.parse_config(Some(&data_dir), true);
-                    }
+                // construct request
+                self.gen_req();
+            }
 
-                    // to backup history file or not
-                    if self.args.rate.as_mut().backup() && self.session_storer.is_some() {
-                        self.close(spd, middleware, pipeline).await;
-                        log::info!("Backup History...");
-                        let path = format!("{}affix", self.args.data_dir);
-                        utils::stored(&path, &mut self.affix, self.session_storer.as_ref());
-                        let path = format!("{}task", self.args.data_dir);
-                        utils::stored(&path, &mut self.task, None);
-                        let path = format!("{}task_tmp", self.args.data_dir);
-                        utils::stored(&path, &mut self.task_tmp, self.session_storer.as_ref());
-                        let path = format!("{}request", self.args.data_dir);
-                        utils::stored(&path, &mut self.req, self.session_storer.as_ref());
-                        let path = format!("{}request_tmp", self.args.data_dir);
-                        utils::stored(&path, &mut self.req_tmp, self.session_storer.as_ref());
-                        let path = format!("{}couple", self.args.data_dir);
-                        utils::stored(&path, &mut self.couple, self.session_storer.as_ref());
-                    }
-                }
+            // join the older jobs, the in-flight ones are cancelled by deadline even if paused
+            self.watch().await;
+
+            self.parse_all(middleware).await;
 
-                _ => unreachable!(),
+            // update Rate
+            let updated = self.args.rate.as_mut().update();
+
+            // update config file in each interval or on SIGHUP
+            let hangup = reload.swap(false, Ordering::Relaxed);
+            if hangup {
+                log::info!("Receive Hangup Signal, Reloading Config ...");
+            }
+            if updated || hangup {
+                let data_dir = self.args.data_dir.clone();
+                self.args.parse_config(Some(&data_dir), true);
+                self.sync_args();
+            }
+
+            if !paused && self.args.rate.as_mut().backup() {
+                // to backup history file or not
+                self.close(actors, middleware, pipeline).await;
+                self.backup();
+            }
+
+            // nothing changed in this round, wait for something to do
+            if self.footprint() == footprint {
+                self.idle(paused).await;
+            }
+        }
+        if let Some(listener) = listener {
+            listener.abort();
+        }
+        self.actors = added;
+        self.handle.set(Status::Stopped);
+        let summary = self.summary();
+        log::info!("Run Summary: {:?}", summary);
+        Ok(summary)
+    }
+}
+
+/// take all entities out of `frontier` to be stored, the frontier itself is kept
+fn drained<T>(frontier: &Vault<Box<dyn Frontier<T>>>) -> Vault<Vec<T>> {
+    Vault::new(frontier.as_mut().drain())
+}
+
+fn shared_storer(session: &Session) -> Box<Storer> {
+    fn storer<'a>(session: &Session, poly: Poly, unit: &'a ()) -> &'a str {
+        match session.lock().unwrap().0 {
+            Some(ref storer) => storer(poly, unit),
+            None => {
+                log::error!("Session Storer Not Provided");
+                ""
             }
         }
-        Ok(())
     }
+    let session = session.clone();
+    Box::new(move |poly, unit| storer(&session, poly, unit))
+}
+
+fn shared_loader(session: &Session) -> Box<Loader> {
+    let session = session.clone();
+    Box::new(move |line| match session.lock().unwrap().1 {
+        Some(ref loader) => loader(line),
+        None => {
+            // not a `Task`, dropped as nothing loaded
+            log::error!("Session Loader Not Provided");
+            Poly::Affix(Affix::default())
+        }
+    })
+}
+
+#[test]
+fn test_gen_req() {
+    use crate::component::affinity::Affinity;
+
+    fn parse(_: Response) -> Parsed<()> {
+        Parsed::new()
+    }
+    let mut app = App::<()>::new();
+    app.args.arg_affix = Some(ArgAffix {
+        is_on: true,
+        affix_min: 0,
+        affix_max: 10,
+        ..ArgAffix::new()
+    });
+    let task = Task::get("https://example.com/")
+        .parser(parse)
+        .body(Body::empty(), "")
+        .unwrap();
+    let stamp = utils::now() + 60.0;
+    let affix = Affix::builder().not_before(stamp).body(Body::empty());
+    app.task.as_mut().push(task);
+    app.affix.as_mut().push(affix.unwrap());
+
+    // held until the affix is available, and woken up then
+    app.gen_req();
+    assert!(app.req.as_ref().is_empty());
+    assert_eq!(app.affix.as_ref().len(), 1);
+    assert_eq!(app.next_wakeup(stamp - 0.5, false), stamp);
+
+    let affix = Affix::builder().delay(0.0).body(Body::empty());
+    app.affix.as_mut().push(affix.unwrap());
+    app.gen_req();
+    assert_eq!(app.req.as_ref().len(), 1);
+    assert_eq!(app.affix.as_ref().len(), 1);
+
+    // coupled only with the affix of its domain, held back otherwise
+    let task = Task::get("https://api.example.org/")
+        .affinity(Affinity::domain("api.example.org"))
+        .parser(parse)
+        .body(Body::empty(), "")
+        .unwrap();
+    app.task.as_mut().push(task);
+    let affix = Affix::builder().domain("example.com").body(Body::empty());
+    app.affix.as_mut().push(affix.unwrap());
+    app.gen_req();
+    assert_eq!(app.req.as_ref().len(), 1);
+    assert_eq!(app.task.as_ref().len(), 1);
+    let affix = Affix::builder().domain("example.org").body(Body::empty());
+    app.affix.as_mut().push(affix.unwrap());
+    app.gen_req();
+    assert_eq!(app.req.as_ref().len(), 2);
+    assert!(app.task.as_ref().is_empty());
+    let couples = app.couple.as_ref();
+    let domains = couples
+        .values()
+        .filter_map(|couple| couple.affix.as_ref()?.domain())
+        .collect::<Vec<_>>();
+    assert_eq!(domains, vec!["example.org"]);
 }
@@ -0,0 +1,110 @@
+//! Events emitted by [App] at runtime, observed by subscribers added with [App::subscribe]
+//!
+//! ```rust,no_run
+//! # use dyer::*;
+//! # fn main() {}
+//! # fn observe<E>(app: &mut App<E>) {
+//! app.subscribe(Box::new(|event: &Event| {
+//!     if let Event::Cancelled { id } = event {
+//!         log::warn!("request {} cancelled", id);
+//!     }
+//! }));
+//! # }
+//! ```
+//!
+//! Note that subscribers are called synchronously in the main loop, keep them cheap.
+//!
+//! [App]: crate::engine::App
+//! [App::subscribe]: crate::engine::App::subscribe
+use crate::engine::stats::Budget;
+use crate::response::FailureKind;
+use http::Uri;
+
+/// the reason why a `Task` is dropped
+#[derive(std::fmt::Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DropReason {
+    /// seen before, see [DupeFilter]
+    ///
+    /// [DupeFilter]: crate::engine::dupe::DupeFilter
+    Duplicate,
+    /// deeper than `max_depth`
+    Depth,
+}
+
+/// events emitted by [App]
+///
+/// [App]: crate::engine::App
+#[derive(std::fmt::Debug, Clone)]
+pub enum Event {
+    /// `App` starts running
+    Started,
+    /// a `Request` is spawned
+    Spawned { id: u64, uri: Uri },
+    /// a `Response` is received
+    Received { id: u64, status: u16, gap: f64 },
+    /// a `Request` failed without `Response`
+    Failed {
+        id: u64,
+        uri: Uri,
+        kind: FailureKind,
+    },
+    /// a `Request` in flight is cancelled for exceeding `join_gap`,
+    /// followed by `Failed` once it is handled
+    Cancelled { id: u64 },
+    /// a `Response` is parsed, with the number of items yielded
+    Parsed {
+        id: u64,
+        tasks: usize,
+        affixs: usize,
+        requests: usize,
+        entities: usize,
+        errors: usize,
+    },
+    /// a `Task` is dropped
+    Dropped { id: u64, reason: DropReason },
+    /// an `Affix` is evicted from the pool for failures or bans
+    Evicted { id: u64 },
+    /// entities and parse-failed `Response` are consumed by `PipeLine`
+    Dumped { entities: usize, errors: usize },
+    /// history files are written into `data_dir`
+    BackedUp,
+    /// a budget is exhausted, followed by `Stopping`
+    Exhausted { budget: Budget },
+    /// `App` starts exiting
+    Stopping,
+    /// the actor is closed and `App` exits
+    Closed,
+}
+
+/// subscriber of [Event]
+pub type Subscriber = dyn Fn(&Event) + Send;
+
+#[test]
+fn test_event() {
+    use crate::component::Task;
+    use crate::engine::App;
+    use std::sync::{Arc, Mutex};
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let mut app = App::<()>::new();
+    let cloned = events.clone();
+    app.subscribe(Box::new(move |event: &Event| {
+        if let Event::Dropped { id, reason } = event {
+            cloned.lock().unwrap().push((*id, *reason));
+        }
+    }));
+    let mut tasks = (0..3)
+        .map(|id| {
+            let mut task = Task::default();
+            task.inner.uri = "https://example.com/".parse().unwrap();
+            task.metat.info.id = id;
+            task
+        })
+        .collect::<Vec<_>>();
+    app.dedup(&mut tasks);
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(
+        *events.lock().unwrap(),
+        vec![(1, DropReason::Duplicate), (2, DropReason::Duplicate)]
+    );
+}
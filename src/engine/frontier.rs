@@ -0,0 +1,630 @@
+//! The queues that schedule [Task], [Request] and other entities of [App]
+//!
+//! [Frontier] is the storage of queued entities, [App] keeps `Task` and `Request` in it, and
+//! it can be replaced with your own storage, eg. backed by a database:
+//!
+//! - [MemoryFrontier], a priority queue in memory, the default of `Request`
+//! - [DiskFrontier], keeps a bounded priority queue in memory and spills the rest into files,
+//!   the default of `Task` that spills into `data_dir/tasks/` once `buf_task` ones are queued
+//! - the ones implementing [Frontier] yourself
+//!
+//! ```rust,ignore
+//! app.task.replace(Box::new(DiskFrontier::new("data/frontier/", 10000, storer, loader)));
+//! ```
+//!
+//! In [MemoryFrontier], entities are ordered by `rank`(the higher the earlier), then by
+//! `able`(the earlier the earlier), then by insertion order. Only the entities available by now
+//! get popped.
+//!
+//! To prevent entities with lower `rank` from starving, every `aging` seconds an entity waits in
+//! the queue, its effective rank rises by 1.
+//!
+//! [Task]: crate::component::Task
+//! [Request]: crate::component::Request
+//! [App]: crate::engine::App
+use crate::component::{Affix, Info, Poly, Request, Task};
+use crate::engine::vault::Vault;
+use crate::utils;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BinaryHeap, VecDeque};
+use std::convert::TryInto;
+
+/// entity that can be scheduled by a [Frontier]
+pub trait Queued {
+    /// the meta data by which the entity is scheduled
+    fn info(&self) -> &Info;
+}
+
+impl Queued for Task {
+    fn info(&self) -> &Info {
+        &self.metat.info
+    }
+}
+
+impl Queued for Request {
+    fn info(&self) -> &Info {
+        &self.metar.info
+    }
+}
+
+impl Queued for Affix {
+    fn info(&self) -> &Info {
+        &self.metap.info
+    }
+}
+
+/// The storage of entities queued in [App], see the [module doc](self)
+///
+/// [App]: crate::engine::App
+pub trait Frontier<T>: Send {
+    /// push an entity into the queue at the time stamp `now`
+    fn push_at(&mut self, item: T, now: f64);
+
+    /// pop the entity with the highest priority that available by `now` and accepted by `f`,
+    /// no more than `limit` entities are checked, the rejected ones are kept as they were.
+    fn pop_with(&mut self, now: f64, limit: usize, f: &mut dyn FnMut(&T) -> bool) -> Option<T>;
+
+    /// the earliest time stamp by which some entity is available
+    fn next_able(&self) -> Option<f64>;
+
+    /// number of entities in the queue
+    fn len(&self) -> usize;
+
+    /// take all entities out of the queue
+    fn drain(&mut self) -> Vec<T>;
+
+    /// visit all entities without taking them out, eg. to back up the queue
+    fn visit(&self, f: &mut dyn FnMut(&T));
+
+    /// set the seconds an entity waits before its effective rank rises by 1,
+    /// ignored if not supported
+    fn set_aging(&mut self, _aging: f64) {}
+
+    /// the queue is empty or not
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// push an entity into the queue
+    fn push(&mut self, item: T) {
+        self.push_at(item, utils::now())
+    }
+
+    /// pop the entity with the highest priority that available by `now`
+    fn pop(&mut self, now: f64) -> Option<T> {
+        self.pop_with(now, 1, &mut |_| true)
+    }
+}
+
+impl<T> Extend<T> for Box<dyn Frontier<T>> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let now = utils::now();
+        iter.into_iter().for_each(|item| self.push_at(item, now));
+    }
+}
+
+/// an entity with its insertion order and time
+struct Entry<T> {
+    able: f64,
+    seq: u64,
+    enqueued: f64,
+    item: T,
+}
+
+impl<T> PartialEq for Entry<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T> Eq for Entry<T> {}
+
+impl<T> PartialOrd for Entry<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Entry<T> {
+    // reversed, the greatest in `BinaryHeap` is the earliest available and inserted
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .able
+            .partial_cmp(&self.able)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+/// A priority queue of entities grouped by `rank`
+pub struct MemoryFrontier<T> {
+    /// entities of the same rank ordered by `able` and insertion order
+    buckets: BTreeMap<i16, BinaryHeap<Entry<T>>>,
+    /// number of entities
+    len: usize,
+    /// insertion counter
+    seq: u64,
+    /// seconds an entity waits before its effective rank rises by 1, `0.0` disables aging
+    pub aging: f64,
+}
+
+impl<T: Queued> MemoryFrontier<T> {
+    /// create an instance of [MemoryFrontier]
+    pub fn new() -> Self {
+        Self {
+            buckets: BTreeMap::new(),
+            len: 0,
+            seq: 0,
+            aging: 0.0,
+        }
+    }
+
+    /// number of entities in the queue
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// the queue is empty or not
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// push an entity into the queue
+    pub fn push(&mut self, item: T) {
+        self.push_at(item, utils::now())
+    }
+
+    /// push an entity into the queue at the time stamp `now`
+    pub fn push_at(&mut self, item: T, now: f64) {
+        let seq = self.seq;
+        self.seq += 1;
+        let entry = Entry {
+            able: item.info().able,
+            seq,
+            enqueued: now,
+            item,
+        };
+        self.insert(item_rank(&entry), entry);
+    }
+
+    fn insert(&mut self, rank: i16, entry: Entry<T>) {
+        self.buckets.entry(rank).or_default().push(entry);
+        self.len += 1;
+    }
+
+    /// the earliest time stamp by which some entity is available
+    pub fn next_able(&self) -> Option<f64> {
+        self.buckets
+            .values()
+            .filter_map(|heap| heap.peek().map(|en| en.able))
+            .fold(None, |acc: Option<f64>, able| {
+                Some(acc.map_or(able, |a| a.min(able)))
+            })
+    }
+
+    /// pop the entity with the highest priority that available by `now`
+    pub fn pop(&mut self, now: f64) -> Option<T> {
+        self.pop_entry(now).map(|(_, en)| en.item)
+    }
+
+    /// pop the entity with the highest priority that available by `now` and accepted by `f`,
+    /// no more than `limit` entities are checked, the rejected ones are kept as they were.
+    pub fn pop_with<F>(&mut self, now: f64, limit: usize, mut f: F) -> Option<T>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let mut rejected = Vec::new();
+        let mut found = None;
+        while rejected.len() < limit {
+            match self.pop_entry(now) {
+                Some((rank, en)) => {
+                    if f(&en.item) {
+                        found = Some(en.item);
+                        break;
+                    }
+                    rejected.push((rank, en));
+                }
+                None => break,
+            }
+        }
+        for (rank, en) in rejected {
+            self.insert(rank, en);
+        }
+        found
+    }
+
+    fn pop_entry(&mut self, now: f64) -> Option<(i16, Entry<T>)> {
+        let aging = self.aging;
+        let mut best: Option<(f64, i16, &Entry<T>)> = None;
+        for (rank, heap) in self.buckets.iter() {
+            let head = match heap.peek() {
+                Some(head) if head.able <= now => head,
+                _ => continue,
+            };
+            let mut eff = *rank as f64;
+            if aging > 0.0 {
+                eff += ((now - head.enqueued) / aging).floor().max(0.0);
+            }
+            let better = match best {
+                None => true,
+                Some((e, r, en)) => match eff.partial_cmp(&e).unwrap_or(Ordering::Equal) {
+                    Ordering::Greater => true,
+                    Ordering::Less => false,
+                    Ordering::Equal => (*rank, head) > (r, en),
+                },
+            };
+            if better {
+                best = Some((eff, *rank, head));
+            }
+        }
+        let rank = best?.1;
+        let heap = self.buckets.get_mut(&rank)?;
+        let entry = heap.pop()?;
+        if heap.is_empty() {
+            self.buckets.remove(&rank);
+        }
+        self.len -= 1;
+        Some((rank, entry))
+    }
+
+    /// iterate over the entities in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.buckets
+            .values()
+            .flat_map(|heap| heap.iter().map(|en| &en.item))
+    }
+
+    /// remove all entities
+    pub fn clear(&mut self) {
+        self.buckets.clear();
+        self.len = 0;
+    }
+}
+
+fn item_rank<T: Queued>(entry: &Entry<T>) -> i16 {
+    entry.item.info().rank
+}
+
+impl<T: Queued> Default for MemoryFrontier<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Queued> Extend<T> for MemoryFrontier<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let now = utils::now();
+        iter.into_iter().for_each(|item| self.push_at(item, now));
+    }
+}
+
+impl<T: Queued> std::iter::FromIterator<T> for MemoryFrontier<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut frontier = Self::new();
+        frontier.extend(iter);
+        frontier
+    }
+}
+
+impl<T> IntoIterator for MemoryFrontier<T> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    /// consume the queue in the order of priority (aging ignored)
+    fn into_iter(self) -> Self::IntoIter {
+        let mut items = Vec::with_capacity(self.len);
+        for (_, heap) in self.buckets.into_iter().rev() {
+            items.extend(heap.into_sorted_vec().into_iter().rev().map(|en| en.item));
+        }
+        items.into_iter()
+    }
+}
+
+impl<T> std::fmt::Debug for MemoryFrontier<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MemoryFrontier")
+            .field("len", &self.len)
+            .field("ranks", &self.buckets.keys().collect::<Vec<_>>())
+            .field("aging", &self.aging)
+            .finish()
+    }
+}
+
+impl<T: Queued + Send> Frontier<T> for MemoryFrontier<T> {
+    fn push_at(&mut self, item: T, now: f64) {
+        MemoryFrontier::push_at(self, item, now)
+    }
+
+    fn pop_with(&mut self, now: f64, limit: usize, f: &mut dyn FnMut(&T) -> bool) -> Option<T> {
+        MemoryFrontier::pop_with(self, now, limit, f)
+    }
+
+    fn next_able(&self) -> Option<f64> {
+        MemoryFrontier::next_able(self)
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn drain(&mut self) -> Vec<T> {
+        let aging = self.aging;
+        let items = std::mem::take(self).into_iter().collect();
+        self.aging = aging;
+        items
+    }
+
+    fn visit(&self, f: &mut dyn FnMut(&T)) {
+        self.iter().for_each(f)
+    }
+
+    fn set_aging(&mut self, aging: f64) {
+        self.aging = aging;
+    }
+}
+
+/// the function that turns an entity into a line of the spilled file
+pub type Storer = dyn for<'a> Fn(Poly, &'a ()) -> &'a str + Send;
+
+/// the function that turns a line of the spilled file into an entity
+pub type Loader = dyn Fn(&str) -> Poly + Send;
+
+/// A [Frontier] that keeps at most `capacity` entities in a [MemoryFrontier], the others are
+/// spilled into numbered files under a directory and loaded back once the memory runs out
+///
+/// Entities in memory are ordered as [MemoryFrontier] does, while the spilled ones are loaded
+/// back in the order they are pushed.
+pub struct DiskFrontier<T> {
+    /// entities in memory, empty only if nothing is queued
+    head: MemoryFrontier<T>,
+    /// entities pushed once `head` is full, spilled when reaching `capacity`
+    tail: Vec<T>,
+    /// directory to store the spilled files
+    dir: String,
+    /// maximal number of entities in `head` and in each file
+    capacity: usize,
+    /// index and number of entities of the spilled files, the earliest first
+    files: VecDeque<(usize, usize)>,
+    /// index of the next file to spill
+    next: usize,
+    storer: Box<Storer>,
+    loader: Box<Loader>,
+}
+
+impl<T> DiskFrontier<T>
+where
+    T: Queued,
+    Poly: From<T> + TryInto<T>,
+{
+    /// create an instance of [DiskFrontier] spilling into `dir`, `storer` and `loader` convert
+    /// an entity from and into a line of file, see [App::session_storer]
+    ///
+    /// [App::session_storer]: crate::engine::App::session_storer
+    pub fn new(dir: &str, capacity: usize, storer: Box<Storer>, loader: Box<Loader>) -> Self {
+        Self {
+            head: MemoryFrontier::new(),
+            tail: Vec::new(),
+            dir: dir.trim_end_matches('/').to_string(),
+            capacity: capacity.max(1),
+            files: VecDeque::new(),
+            next: 0,
+            storer,
+            loader,
+        }
+    }
+
+    fn path(&self, index: usize) -> String {
+        format!("{}/{}", self.dir, index)
+    }
+
+    /// write `tail` into a new file, the directory is created on the first spill
+    fn spill(&mut self) {
+        if self.next == 0 {
+            if let Err(e) = std::fs::create_dir_all(&self.dir) {
+                log::error!("Failed to create directory {}: {}", self.dir, e);
+            }
+        }
+        let path = self.path(self.next);
+        let len = self.tail.len();
+        let mut items = Vault::new(std::mem::take(&mut self.tail));
+        utils::stored(&path, &mut items, Some(&self.storer));
+        self.files.push_back((self.next, len));
+        self.next += 1;
+    }
+
+    /// load the earliest file, or `tail` if nothing spilled, once `head` runs out
+    fn refill(&mut self) {
+        if !self.head.is_empty() {
+            return;
+        }
+        let items = match self.files.pop_front() {
+            Some((index, _)) => {
+                let path = self.path(index);
+                let items: Vec<T> = utils::load(&path, Some(&self.loader));
+                if let Err(e) = std::fs::remove_file(&path) {
+                    log::warn!("Failed to remove {}: {}", path, e);
+                }
+                items
+            }
+            None => std::mem::take(&mut self.tail),
+        };
+        self.head.extend(items);
+    }
+}
+
+impl<T> Frontier<T> for DiskFrontier<T>
+where
+    T: Queued + Send,
+    Poly: From<T> + TryInto<T>,
+{
+    fn push_at(&mut self, item: T, now: f64) {
+        if self.files.is_empty() && self.tail.is_empty() && self.head.len() < self.capacity {
+            self.head.push_at(item, now);
+            return;
+        }
+        self.tail.push(item);
+        if self.tail.len() >= self.capacity {
+            self.spill();
+        }
+    }
+
+    /// only the entities in memory are checked
+    fn pop_with(&mut self, now: f64, limit: usize, f: &mut dyn FnMut(&T) -> bool) -> Option<T> {
+        let item = self.head.pop_with(now, limit, f);
+        self.refill();
+        item
+    }
+
+    fn next_able(&self) -> Option<f64> {
+        self.head.next_able()
+    }
+
+    fn len(&self) -> usize {
+        self.head.len() + self.tail.len() + self.files.iter().map(|(_, n)| n).sum::<usize>()
+    }
+
+    fn drain(&mut self) -> Vec<T> {
+        let mut items = Frontier::drain(&mut self.head);
+        while let Some((index, _)) = self.files.pop_front() {
+            let path = self.path(index);
+            items.extend(utils::load::<T>(&path, Some(&self.loader)));
+            if let Err(e) = std::fs::remove_file(&path) {
+                log::warn!("Failed to remove {}: {}", path, e);
+            }
+        }
+        items.append(&mut self.tail);
+        items
+    }
+
+    /// the spilled entities are loaded from their files, which are kept
+    fn visit(&self, f: &mut dyn FnMut(&T)) {
+        self.head.iter().for_each(&mut *f);
+        for (index, _) in self.files.iter() {
+            utils::load::<T>(&self.path(*index), Some(&self.loader))
+                .iter()
+                .for_each(&mut *f);
+        }
+        self.tail.iter().for_each(f);
+    }
+
+    fn set_aging(&mut self, aging: f64) {
+        self.head.aging = aging;
+    }
+}
+
+impl<T> std::fmt::Debug for DiskFrontier<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DiskFrontier")
+            .field("head", &self.head)
+            .field("tail", &self.tail.len())
+            .field("dir", &self.dir)
+            .field("capacity", &self.capacity)
+            .field("files", &self.files)
+            .finish()
+    }
+}
+
+#[test]
+fn test_frontier() {
+    let req = |rank: i16, able: f64, id: u64| {
+        let mut req = Request::default();
+        req.metar.info.rank = rank;
+        req.metar.info.able = able;
+        req.metar.info.id = id;
+        req
+    };
+    let mut frontier = MemoryFrontier::new();
+    frontier.push_at(req(0, 1.0, 1), 1.0);
+    frontier.push_at(req(1, 5.0, 2), 1.0);
+    frontier.push_at(req(1, 2.0, 3), 1.0);
+    frontier.push_at(req(0, 1.0, 4), 1.0);
+    frontier.push_at(req(-1, 1.0, 5), 1.0);
+    assert_eq!(frontier.len(), 5);
+    assert_eq!(frontier.next_able(), Some(1.0));
+    // rank first, then able
+    assert_eq!(frontier.pop(10.0).map(|r| r.metar.info.id), Some(3));
+    assert_eq!(frontier.pop(10.0).map(|r| r.metar.info.id), Some(2));
+    // then insertion order
+    assert_eq!(frontier.pop(10.0).map(|r| r.metar.info.id), Some(1));
+    // rejected ones are kept
+    assert!(frontier.pop_with(10.0, 10, |_| false).is_none());
+    assert_eq!(frontier.len(), 2);
+    assert_eq!(
+        frontier
+            .pop_with(10.0, 10, |r| r.metar.info.id == 5)
+            .map(|r| r.metar.info.id),
+        Some(5)
+    );
+    assert_eq!(frontier.pop(10.0).map(|r| r.metar.info.id), Some(4));
+    assert!(frontier.is_empty());
+
+    // not available
+    frontier.push_at(req(0, 20.0, 6), 1.0);
+    assert!(frontier.pop(10.0).is_none());
+    assert_eq!(frontier.next_able(), Some(20.0));
+    assert_eq!(frontier.pop(20.0).map(|r| r.metar.info.id), Some(6));
+
+    // aging lifts the waiting request
+    frontier.aging = 5.0;
+    frontier.push_at(req(0, 1.0, 7), 1.0);
+    frontier.push_at(req(1, 1.0, 8), 20.0);
+    assert_eq!(frontier.pop(21.0).map(|r| r.metar.info.id), Some(7));
+    assert_eq!(frontier.pop(21.0).map(|r| r.metar.info.id), Some(8));
+}
+
+#[test]
+fn test_disk_frontier() {
+    fn req(rank: i16, id: u64) -> Request {
+        let mut req = Request::default();
+        req.metar.info.rank = rank;
+        req.metar.info.able = 0.0;
+        req.metar.info.id = id;
+        req
+    }
+    // the lines outlive the storer, so they are static rather than formatted
+    const LINES: [&str; 9] = ["0", "1", "2", "3", "4", "5", "6", "7", "8"];
+    fn storer(poly: Poly, _: &()) -> &str {
+        match poly {
+            Poly::Request(req) => LINES[req.metar.info.id as usize],
+            _ => "",
+        }
+    }
+    fn loader(line: &str) -> Poly {
+        let id = line.parse().unwrap();
+        Poly::Request(req(id as i16, id))
+    }
+    let dir = std::env::temp_dir().join(format!("dyer-frontier-{}", std::process::id()));
+    let dir = dir.to_str().unwrap().to_string();
+    let mut frontier: Box<dyn Frontier<Request>> = Box::new(DiskFrontier::new(
+        &dir,
+        2,
+        Box::new(storer),
+        Box::new(loader),
+    ));
+    frontier.extend((1..=7).map(|id| req(id as i16, id)));
+    assert_eq!(frontier.len(), 7);
+    // 3 and 4 spilled into a file, 5 and 6 as well, 7 kept in memory
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 2);
+    // visited without taking them out
+    let mut ids = Vec::new();
+    frontier.visit(&mut |r| ids.push(r.metar.info.id));
+    ids.sort_unstable();
+    assert_eq!(ids, (1..=7).collect::<Vec<_>>());
+    assert_eq!(frontier.len(), 7);
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 2);
+    // ordered by rank in memory, then loaded back in the order pushed
+    let ids = (0..5)
+        .filter_map(|_| frontier.pop(1.0).map(|r| r.metar.info.id))
+        .collect::<Vec<_>>();
+    assert_eq!(ids, vec![2, 1, 4, 3, 6]);
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+    frontier.push(req(0, 8));
+    let ids = frontier
+        .drain()
+        .iter()
+        .map(|r| r.metar.info.id)
+        .collect::<Vec<_>>();
+    assert_eq!(ids, vec![5, 7, 8]);
+    assert!(frontier.is_empty());
+    std::fs::remove_dir_all(&dir).ok();
+}
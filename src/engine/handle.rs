@@ -0,0 +1,204 @@
+//! A cloneable handle to control the running [App] from elsewhere,
+//! eg. another task or thread of a long-running service.
+//!
+//! ```rust,no_run
+//! # use dyer::*;
+//! # fn main() {}
+//! # async fn embed<E>(app: &mut App<E>) {
+//! let handle = app.handle();
+//! tokio::spawn(async move {
+//!     handle.pause();
+//!     // ...
+//!     handle.resume();
+//!     // ...
+//!     handle.stop();
+//! });
+//! # }
+//! ```
+//!
+//! [App]: crate::engine::App
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+
+/// status of [App]
+///
+/// [App]: crate::engine::App
+#[derive(std::fmt::Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    /// not started yet
+    Ready,
+    /// spawning requests
+    Running,
+    /// no request spawned, the in-flight ones and queues are kept
+    Paused,
+    /// exiting, joining the in-flight requests and backing up
+    Stopping,
+    /// exited
+    Stopped,
+}
+
+impl Status {
+    fn from_usize(n: usize) -> Self {
+        match n {
+            0 => Status::Ready,
+            1 => Status::Running,
+            2 => Status::Paused,
+            3 => Status::Stopping,
+            _ => Status::Stopped,
+        }
+    }
+}
+
+/// A cloneable handle to control [App]
+///
+/// [App]: crate::engine::App
+#[derive(std::fmt::Debug, Clone)]
+pub struct Handle {
+    status: Arc<AtomicUsize>,
+    /// wake up the idle `App`
+    pub(crate) wake: Arc<Notify>,
+}
+
+impl Handle {
+    /// create an instance of [Handle]
+    pub fn new() -> Self {
+        Self {
+            status: Arc::new(AtomicUsize::new(Status::Ready as usize)),
+            wake: Arc::new(Notify::new()),
+        }
+    }
+
+    /// get the status of [App]
+    ///
+    /// [App]: crate::engine::App
+    pub fn status(&self) -> Status {
+        Status::from_usize(self.status.load(Ordering::Acquire))
+    }
+
+    /// stop the [App] gracefully, the same as receiving Ctrl+c
+    ///
+    /// [App]: crate::engine::App
+    pub fn stop(&self) {
+        let stopped = self
+            .status
+            .fetch_update(
+                Ordering::AcqRel,
+                Ordering::Acquire,
+                |n| match Status::from_usize(n) {
+                    Status::Stopping | Status::Stopped => None,
+                    _ => Some(Status::Stopping as usize),
+                },
+            );
+        if stopped.is_ok() {
+            self.wake.notify_one();
+        }
+    }
+
+    /// stop spawning requests, `false` returned if it is not running or ready
+    pub fn pause(&self) -> bool {
+        self.status
+            .fetch_update(
+                Ordering::AcqRel,
+                Ordering::Acquire,
+                |n| match Status::from_usize(n) {
+                    Status::Ready | Status::Running => Some(Status::Paused as usize),
+                    _ => None,
+                },
+            )
+            .is_ok()
+    }
+
+    /// resume spawning requests, `false` returned if it is not paused
+    pub fn resume(&self) -> bool {
+        let resumed = self.swap(Status::Paused, Status::Running);
+        if resumed {
+            self.wake.notify_one();
+        }
+        resumed
+    }
+
+    /// set the status as `new` if it is `current`
+    pub(crate) fn swap(&self, current: Status, new: Status) -> bool {
+        self.status
+            .compare_exchange(
+                current as usize,
+                new as usize,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            )
+            .is_ok()
+    }
+
+    pub(crate) fn set(&self, status: Status) {
+        self.status.store(status as usize, Ordering::Release);
+    }
+
+    /// stop on SIGINT or SIGTERM, set `reload` on SIGHUP, and wake up the `App`
+    #[cfg(unix)]
+    pub(crate) fn listen(&self, reload: Arc<AtomicBool>) -> std::io::Result<JoinHandle<()>> {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut interrupt = signal(SignalKind::interrupt())?;
+        let mut terminate = signal(SignalKind::terminate())?;
+        let mut hangup = signal(SignalKind::hangup())?;
+        let handle = self.clone();
+        Ok(tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = interrupt.recv() => {
+                        log::info!("Receive Ctrl+c Signal, Preparing Exit ...");
+                        handle.stop();
+                    }
+                    _ = terminate.recv() => {
+                        log::info!("Receive Terminate Signal, Preparing Exit ...");
+                        handle.stop();
+                    }
+                    _ = hangup.recv() => {
+                        reload.store(true, Ordering::Relaxed);
+                        handle.wake.notify_one();
+                    }
+                }
+            }
+        }))
+    }
+
+    /// stop on Ctrl+c, config is reloaded only by `rate.interval` without SIGHUP
+    #[cfg(not(unix))]
+    pub(crate) fn listen(&self, _reload: Arc<AtomicBool>) -> std::io::Result<JoinHandle<()>> {
+        let handle = self.clone();
+        Ok(tokio::spawn(async move {
+            while tokio::signal::ctrl_c().await.is_ok() {
+                log::info!("Receive Ctrl+c Signal, Preparing Exit ...");
+                handle.stop();
+            }
+        }))
+    }
+}
+
+impl Default for Handle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[test]
+fn test_handle() {
+    let handle = Handle::new();
+    let other = handle.clone();
+    assert_eq!(handle.status(), Status::Ready);
+    assert!(handle.swap(Status::Ready, Status::Running));
+    assert!(other.pause());
+    assert_eq!(handle.status(), Status::Paused);
+    assert!(!other.pause());
+    assert!(other.resume());
+    assert!(!other.resume());
+    assert_eq!(handle.status(), Status::Running);
+    other.stop();
+    assert_eq!(handle.status(), Status::Stopping);
+    assert!(!handle.pause());
+    handle.set(Status::Stopped);
+    other.stop();
+    assert_eq!(handle.status(), Status::Stopped);
+}
@@ -0,0 +1,313 @@
+//! Book-keeping of in-flight requests for each host,
+//! with which the engine keeps the concurrency and the pace towards one host in control.
+//!
+//! Requests are grouped by host name or by registrable domain, see [ArgHost] for more.
+//!
+//! With auto-throttle on, the limit of each host is adjusted by each `Response` or failure
+//! received from it. The delay approaches `latency / target`, doubled once an error occurs,
+//! the concurrency rises by 1 on success and is halved on error, both kept within the bounds
+//! of [ArgThrottle]. Neither is eased while the error rate exceeds `error_rate`.
+//!
+//! [ArgHost]: crate::engine::arg::ArgHost
+//! [ArgThrottle]: crate::engine::arg::ArgThrottle
+use crate::engine::arg::{ArgHost, ArgThrottle};
+use http::Uri;
+use std::collections::HashMap;
+
+/// second-level labels that are commonly registered under a country code top-level domain,
+/// eg. `co.uk`, `com.cn`
+const SLDS: [&str; 8] = ["co", "com", "net", "org", "gov", "edu", "ac", "ne"];
+
+/// get the key by which requests are grouped, the host name if `by_domain` is false,
+/// otherwise the registrable domain of the host.
+///
+/// Note that the registrable domain is approximated without the public suffix list:
+/// the last two labels are taken, or the last three if the host ends with a
+/// country code top-level domain under a common second-level label, eg. `example.co.uk`
+pub fn host_key(uri: &Uri, by_domain: bool) -> String {
+    let host = uri.host().unwrap_or("").to_lowercase();
+    if !by_domain || host.parse::<std::net::IpAddr>().is_ok() || host.starts_with('[') {
+        return host;
+    }
+    let labels = host.split('.').collect::<Vec<_>>();
+    let len = labels.len();
+    if len <= 2 {
+        return host;
+    }
+    let take = if labels[len - 1].len() == 2 && SLDS.contains(&labels[len - 2]) {
+        3
+    } else {
+        2
+    };
+    labels[len.saturating_sub(take)..].join(".")
+}
+
+/// weight of the latest observation in the error rate
+const ERROR_WEIGHT: f64 = 0.1;
+
+/// the limit of a host adjusted by auto-throttle
+#[derive(std::fmt::Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Throttle {
+    /// maximal number of requests in flight
+    pub(crate) concurrency: usize,
+    /// minimal duration in seconds between two requests spawned
+    pub(crate) delay: f64,
+    /// moving average of errors
+    errors: f64,
+}
+
+impl Throttle {
+    /// start from the limit `(concurrency, delay)` within the bounds
+    fn new(limit: (usize, f64), arg: &ArgThrottle) -> Self {
+        let concurrency = match limit.0 {
+            0 => arg.concurrency_max,
+            n => n,
+        };
+        Self {
+            concurrency: concurrency
+                .min(arg.concurrency_max)
+                .max(arg.concurrency_min),
+            delay: limit.1.min(arg.delay_max).max(arg.delay_min),
+            errors: 0.0,
+        }
+    }
+
+    /// adjust by a request taking `latency` seconds, failed or not
+    fn update(&mut self, latency: f64, failed: bool, arg: &ArgThrottle) {
+        let error = if failed { 1.0 } else { 0.0 };
+        self.errors = self.errors * (1.0 - ERROR_WEIGHT) + error * ERROR_WEIGHT;
+        let target = latency.max(0.0) / arg.target.max(f64::EPSILON);
+        let mut delay = (self.delay + target) / 2.0;
+        if failed {
+            delay = (self.delay * 2.0).max(target);
+            self.concurrency /= 2;
+        } else if self.errors > arg.error_rate {
+            delay = delay.max(self.delay);
+        } else {
+            self.concurrency += 1;
+        }
+        self.delay = delay.min(arg.delay_max).max(arg.delay_min);
+        self.concurrency = self
+            .concurrency
+            .min(arg.concurrency_max)
+            .max(arg.concurrency_min);
+    }
+}
+
+/// get the concurrency and delay applied to `host`, adjusted by `throttles` if auto-throttle is on
+fn limit(throttles: &HashMap<String, Throttle>, host: &str, arg: &ArgHost) -> (usize, f64) {
+    match throttles.get(host) {
+        Some(throttle) if arg.throttle.is_on => (throttle.concurrency, throttle.delay),
+        _ => arg.limit(host),
+    }
+}
+
+/// the requests in flight and the time stamp of latest request of each host
+#[derive(std::fmt::Debug, Default)]
+pub(crate) struct Hosts {
+    /// number of requests in flight of each host
+    active: HashMap<String, usize>,
+    /// time stamp at which the latest request to the host is spawned
+    last: HashMap<String, f64>,
+    /// id of request in flight and its host
+    inflight: HashMap<u64, String>,
+    /// limit of each host adjusted by auto-throttle
+    throttles: HashMap<String, Throttle>,
+}
+
+impl Hosts {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// get the concurrency and delay applied to `host`, adjusted if auto-throttle is on
+    pub(crate) fn limit(&self, host: &str, arg: &ArgHost) -> (usize, f64) {
+        limit(&self.throttles, host, arg)
+    }
+
+    /// adjust the limit of the host that request `id` is sent to, by its `latency`
+    /// and whether it failed, it takes no effect if auto-throttle is off
+    pub(crate) fn observe(&mut self, id: u64, latency: f64, failed: bool, arg: &ArgHost) {
+        if !arg.throttle.is_on {
+            return;
+        }
+        let host = match self.inflight.get(&id) {
+            Some(host) => host,
+            None => return,
+        };
+        self.throttles
+            .entry(host.clone())
+            .or_insert_with(|| Throttle::new(arg.limit(host), &arg.throttle))
+            .update(latency, failed, &arg.throttle);
+    }
+
+    /// drop the limits adjusted by auto-throttle once it is off
+    pub(crate) fn configure(&mut self, arg: &ArgHost) {
+        if !arg.throttle.is_on {
+            self.throttles.clear();
+        }
+    }
+
+    /// the limit of `host` adjusted by auto-throttle
+    #[cfg(test)]
+    pub(crate) fn throttle(&self, host: &str) -> Option<Throttle> {
+        self.throttles.get(host).copied()
+    }
+
+    /// whether a request to `host` is allowed to be spawned at `now`
+    pub(crate) fn available(&self, host: &str, arg: &ArgHost, now: f64) -> bool {
+        let (concurrency, delay) = self.limit(host, arg);
+        if concurrency > 0 && self.active.get(host).copied().unwrap_or(0) >= concurrency {
+            return false;
+        }
+        match self.last.get(host) {
+            Some(last) => now - last >= delay,
+            None => true,
+        }
+    }
+
+    /// try to occupy a slot of `host` for request `id`,
+    /// `false` returned if the host is busy for now
+    pub(crate) fn acquire(&mut self, id: u64, host: String, arg: &ArgHost, now: f64) -> bool {
+        if !self.available(&host, arg, now) {
+            return false;
+        }
+        self.release(id);
+        self.prune(arg, now);
+        *self.active.entry(host.clone()).or_insert(0) += 1;
+        self.last.insert(host.clone(), now);
+        self.inflight.insert(id, host);
+        true
+    }
+
+    /// release the slot occupied by request `id` if any
+    pub(crate) fn release(&mut self, id: u64) {
+        if let Some(host) = self.inflight.remove(&id) {
+            if let Some(n) = self.active.get_mut(&host) {
+                *n = n.saturating_sub(1);
+                if *n == 0 {
+                    self.active.remove(&host);
+                }
+            }
+        }
+    }
+
+    /// forget the idle hosts whose delay has expired by `now`, along with their throttles
+    fn prune(&mut self, arg: &ArgHost, now: f64) {
+        let (active, throttles) = (&self.active, &self.throttles);
+        self.last.retain(|host, last| {
+            active.contains_key(host) || now - *last < limit(throttles, host, arg).1
+        });
+        let last = &self.last;
+        self.throttles
+            .retain(|host, _| active.contains_key(host) || last.contains_key(host));
+    }
+
+    /// the earliest time stamp after `now` by which a host delayed for now is available
+    pub(crate) fn next_free(&self, arg: &ArgHost, now: f64) -> Option<f64> {
+        self.last
+            .iter()
+            .map(|(host, last)| last + self.limit(host, arg).1)
+            .filter(|&free| free > now)
+            .fold(None, |acc: Option<f64>, free| {
+                Some(acc.map_or(free, |a| a.min(free)))
+            })
+    }
+
+    /// number of requests in flight to `host`
+    #[cfg(test)]
+    pub(crate) fn active(&self, host: &str) -> usize {
+        self.active.get(host).copied().unwrap_or(0)
+    }
+}
+
+#[test]
+fn test_hosts() {
+    let uri: Uri = "https://www.example.co.uk/path".parse().unwrap();
+    assert_eq!(host_key(&uri, false), "www.example.co.uk");
+    assert_eq!(host_key(&uri, true), "example.co.uk");
+    let uri: Uri = "https://a.b.example.com/".parse().unwrap();
+    assert_eq!(host_key(&uri, true), "example.com");
+    let uri: Uri = "http://127.0.0.1:8080/".parse().unwrap();
+    assert_eq!(host_key(&uri, true), "127.0.0.1");
+
+    let mut arg = ArgHost::new();
+    arg.concurrency = 1;
+    arg.delay = 2.0;
+    let mut hosts = Hosts::new();
+    assert!(hosts.acquire(1, "example.com".into(), &arg, 10.0));
+    // busy both in concurrency and delay
+    assert!(!hosts.acquire(2, "example.com".into(), &arg, 13.0));
+    hosts.release(1);
+    assert!(!hosts.acquire(2, "example.com".into(), &arg, 11.0));
+    assert!(hosts.acquire(2, "example.com".into(), &arg, 13.0));
+    assert_eq!(hosts.active("example.com"), 1);
+    // per host override
+    arg.overrides.insert(
+        "example.org".into(),
+        crate::engine::arg::HostLimit {
+            concurrency: Some(2),
+            delay: Some(0.0),
+            ..Default::default()
+        },
+    );
+    assert!(hosts.acquire(3, "example.org".into(), &arg, 13.0));
+    assert!(hosts.acquire(4, "example.org".into(), &arg, 13.0));
+    assert!(!hosts.acquire(5, "example.org".into(), &arg, 13.0));
+    // example.com delayed until 15.0, example.org not delayed
+    assert_eq!(hosts.next_free(&arg, 13.0), Some(15.0));
+    assert_eq!(hosts.next_free(&arg, 15.0), None);
+    // idle hosts forgotten once their delay expires
+    hosts.release(2);
+    hosts.release(3);
+    hosts.release(4);
+    assert!(hosts.acquire(9, "example.edu".into(), &arg, 14.0));
+    assert_eq!(hosts.last.len(), 2);
+    hosts.release(9);
+    assert!(hosts.acquire(10, "example.gov".into(), &arg, 17.0));
+    assert_eq!(hosts.last.len(), 1);
+    hosts.release(10);
+
+    // auto-throttle, starting from the limit of `ArgHost`
+    arg.throttle.is_on = true;
+    arg.throttle.concurrency_max = 4;
+    arg.throttle.delay_max = 10.0;
+    let host = "example.net";
+    assert!(hosts.acquire(6, host.into(), &arg, 20.0));
+    hosts.observe(6, 1.0, false, &arg);
+    let throttle = hosts.throttle(host).unwrap();
+    assert_eq!(throttle.concurrency, 2);
+    assert_eq!(throttle.delay, 1.25);
+    for _ in 0..20 {
+        hosts.observe(6, 1.0, false, &arg);
+    }
+    let throttle = hosts.throttle(host).unwrap();
+    assert_eq!(throttle.concurrency, 4);
+    assert!((throttle.delay - 0.5).abs() < 1e-3);
+    assert_eq!(hosts.limit(host, &arg), (4, throttle.delay));
+    // backed off on error, not eased until the errors fade
+    hosts.observe(6, 1.0, true, &arg);
+    hosts.observe(6, 1.0, true, &arg);
+    let throttle = hosts.throttle(host).unwrap();
+    assert_eq!(throttle.concurrency, 1);
+    assert!(throttle.delay > 1.9);
+    hosts.observe(6, 0.0, false, &arg);
+    assert_eq!(
+        hosts.throttle(host).unwrap(),
+        Throttle {
+            errors: throttle.errors * 0.9,
+            ..throttle
+        }
+    );
+    // idle host forgotten along with its throttle
+    hosts.release(6);
+    assert!(hosts.acquire(7, "example.edu".into(), &arg, 40.0));
+    assert!(hosts.throttle(host).is_none());
+    hosts.observe(7, 1.0, false, &arg);
+    assert!(hosts.throttle("example.edu").is_some());
+    // static limit applied once off, the throttles dropped
+    arg.throttle.is_on = false;
+    assert_eq!(hosts.limit("example.edu", &arg), (1, 2.0));
+    hosts.configure(&arg);
+    assert!(hosts.throttle("example.edu").is_none());
+}
@@ -0,0 +1,284 @@
+//! Token-bucket rate limit of the requests spawned, both overall and for each host
+//!
+//! A bucket holds at most `burst` tokens and gains `rps` tokens per second, each request spawned
+//! takes one token from the overall bucket and one from the bucket of its host, and waits while
+//! either is empty. So no more than `burst` requests are spawned at once and `rps` per second in
+//! the long run. Once `rate.rps` is set, the overall bucket replaces the load model of [ArgRate].
+//!
+//! The time is read from a [Clock], which is injectable for testing.
+//!
+//! [ArgRate]: crate::engine::arg::ArgRate
+use crate::engine::arg::ArgHost;
+use crate::utils;
+use std::collections::HashMap;
+
+/// the source of time stamps in seconds
+pub trait Clock: Send + Sync {
+    /// the time stamp for now
+    fn now(&self) -> f64;
+}
+
+/// the clock of the system, see [utils::now]
+///
+/// [utils::now]: crate::utils::now
+#[derive(std::fmt::Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> f64 {
+        utils::now()
+    }
+}
+
+impl<F> Clock for F
+where
+    F: Fn() -> f64 + Send + Sync,
+{
+    fn now(&self) -> f64 {
+        self()
+    }
+}
+
+/// a bucket gaining `rps` tokens per second, holding at most `burst` ones
+#[derive(std::fmt::Debug, Clone, Copy, PartialEq)]
+pub struct TokenBucket {
+    /// tokens gained per second
+    pub rps: f64,
+    /// maximal tokens held, at least 1
+    pub burst: f64,
+    /// tokens held by `stamp`
+    tokens: f64,
+    /// time stamp at which `tokens` is updated
+    stamp: f64,
+}
+
+impl TokenBucket {
+    /// create a full bucket at `now`
+    pub fn new(rps: f64, burst: f64, now: f64) -> Self {
+        let burst = burst.max(1.0);
+        Self {
+            rps,
+            burst,
+            tokens: burst,
+            stamp: now,
+        }
+    }
+
+    /// change the `rps` and `burst` at `now`, tokens exceeding the new `burst` are dropped
+    pub fn set(&mut self, rps: f64, burst: f64, now: f64) {
+        self.refill(now);
+        self.rps = rps;
+        self.burst = burst.max(1.0);
+        self.tokens = self.tokens.min(self.burst);
+    }
+
+    /// add the tokens gained since last update
+    fn refill(&mut self, now: f64) {
+        if now > self.stamp {
+            self.tokens = (self.tokens + (now - self.stamp) * self.rps).min(self.burst);
+            self.stamp = now;
+        }
+    }
+
+    /// number of tokens held at `now`
+    pub fn tokens(&mut self, now: f64) -> f64 {
+        self.refill(now);
+        self.tokens
+    }
+
+    /// take a token at `now`, `false` returned if none is held
+    pub fn take(&mut self, now: f64) -> bool {
+        if self.tokens(now) < 1.0 {
+            return false;
+        }
+        self.tokens -= 1.0;
+        true
+    }
+
+    /// the earliest time stamp by which a token is held
+    pub fn next_able(&self) -> f64 {
+        if self.tokens >= 1.0 {
+            self.stamp
+        } else {
+            self.stamp + (1.0 - self.tokens) / self.rps.max(f64::EPSILON)
+        }
+    }
+}
+
+/// the overall bucket and the bucket of each host
+pub(crate) struct RateLimiter {
+    clock: Box<dyn Clock>,
+    /// the overall bucket, `None` if unlimited
+    global: Option<TokenBucket>,
+    /// bucket of each limited host, the full ones are dropped as new ones are full as well
+    hosts: HashMap<String, TokenBucket>,
+}
+
+impl RateLimiter {
+    /// create an instance reading time from the system
+    pub(crate) fn new() -> Self {
+        Self::with_clock(SystemClock)
+    }
+
+    /// create an instance reading time from `clock`
+    pub(crate) fn with_clock<C: Clock + 'static>(clock: C) -> Self {
+        Self {
+            clock: Box::new(clock),
+            global: None,
+            hosts: HashMap::new(),
+        }
+    }
+
+    /// whether the overall bucket following `rps` and `burst` holds a token for now,
+    /// `0.0` rps means unlimited
+    pub(crate) fn ready(&mut self, rps: f64, burst: f64) -> bool {
+        let now = self.clock.now();
+        match sync(&mut self.global, rps, burst, now) {
+            Some(bucket) => bucket.tokens(now) >= 1.0,
+            None => true,
+        }
+    }
+
+    /// whether the bucket of `host` holds a token for now
+    pub(crate) fn host_ready(&mut self, host: &str, arg: &ArgHost) -> bool {
+        let now = self.clock.now();
+        let (rps, burst) = arg.rate(host);
+        let mut bucket = self.hosts.remove(host);
+        let ready = match sync(&mut bucket, rps, burst, now) {
+            Some(bucket) => bucket.tokens(now) >= 1.0,
+            None => true,
+        };
+        match bucket {
+            Some(bucket) if bucket.tokens < bucket.burst => {
+                self.hosts.insert(host.to_string(), bucket);
+            }
+            _ => {}
+        }
+        ready
+    }
+
+    /// take a token from the overall bucket and that of `host`,
+    /// the buckets of other hosts refilled to full are dropped
+    pub(crate) fn take(&mut self, host: &str, arg: &ArgHost) {
+        let now = self.clock.now();
+        if let Some(bucket) = self.global.as_mut() {
+            bucket.take(now);
+        }
+        let (rps, burst) = arg.rate(host);
+        let mut bucket = self.hosts.remove(host);
+        if let Some(bucket) = sync(&mut bucket, rps, burst, now) {
+            bucket.take(now);
+        }
+        self.hosts
+            .retain(|_, bucket| bucket.tokens(now) < bucket.burst);
+        if let Some(bucket) = bucket {
+            self.hosts.insert(host.to_string(), bucket);
+        }
+    }
+
+    /// the earliest time stamp by which the overall bucket holds a token
+    pub(crate) fn next_able(&self) -> Option<f64> {
+        self.global.as_ref().map(TokenBucket::next_able)
+    }
+
+    /// the earliest time stamp by which an empty bucket of host holds a token
+    pub(crate) fn next_free(&self) -> Option<f64> {
+        self.hosts
+            .values()
+            .filter(|bucket| bucket.tokens < 1.0)
+            .map(TokenBucket::next_able)
+            .fold(None, |acc: Option<f64>, free| {
+                Some(acc.map_or(free, |a| a.min(free)))
+            })
+    }
+}
+
+impl std::fmt::Debug for RateLimiter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RateLimiter")
+            .field("global", &self.global)
+            .field("hosts", &self.hosts)
+            .finish()
+    }
+}
+
+/// keep `bucket` in line with `rps` and `burst`, dropped if `rps` is not positive
+fn sync(
+    bucket: &mut Option<TokenBucket>,
+    rps: f64,
+    burst: f64,
+    now: f64,
+) -> Option<&mut TokenBucket> {
+    if rps <= 0.0 {
+        *bucket = None;
+        return None;
+    }
+    match bucket {
+        Some(en) if en.rps != rps || en.burst != burst.max(1.0) => en.set(rps, burst, now),
+        Some(_) => {}
+        None => *bucket = Some(TokenBucket::new(rps, burst, now)),
+    }
+    bucket.as_mut()
+}
+
+#[test]
+fn test_limiter() {
+    use std::sync::{Arc, Mutex};
+
+    let mut bucket = TokenBucket::new(2.0, 3.0, 10.0);
+    assert!(bucket.take(10.0));
+    assert!(bucket.take(10.0));
+    assert!(bucket.take(10.0));
+    assert!(!bucket.take(10.0));
+    assert_eq!(bucket.next_able(), 10.5);
+    assert!(bucket.take(10.5));
+    // never exceeds the burst
+    assert_eq!(bucket.tokens(100.0), 3.0);
+    bucket.set(1.0, 0.0, 100.0);
+    assert_eq!(bucket.tokens(100.0), 1.0);
+
+    // driven by an injected clock
+    let clock = Arc::new(Mutex::new(0.0));
+    let now = clock.clone();
+    let mut limiter = RateLimiter::with_clock(move || *now.lock().unwrap());
+    let mut arg = ArgHost::new();
+    arg.rps = 1.0;
+    arg.burst = 2.0;
+    assert!(limiter.ready(0.0, 1.0));
+    assert_eq!(limiter.next_able(), None);
+    assert!(limiter.ready(4.0, 1.0));
+    assert!(limiter.host_ready("example.com", &arg));
+    // the full bucket is not kept
+    assert!(limiter.hosts.is_empty());
+    limiter.take("example.com", &arg);
+    assert!(!limiter.ready(4.0, 1.0));
+    assert_eq!(limiter.next_able(), Some(0.25));
+    assert!(limiter.host_ready("example.com", &arg));
+    assert_eq!(limiter.next_free(), None);
+    *clock.lock().unwrap() = 0.25;
+    assert!(limiter.ready(4.0, 1.0));
+    limiter.take("example.com", &arg);
+    assert!(!limiter.host_ready("example.com", &arg));
+    assert_eq!(limiter.next_free(), Some(1.0));
+    // other hosts are not affected, and per host override taken
+    assert!(limiter.host_ready("example.org", &arg));
+    arg.overrides.insert(
+        "example.com".into(),
+        crate::engine::arg::HostLimit {
+            rps: Some(0.0),
+            ..Default::default()
+        },
+    );
+    assert!(limiter.host_ready("example.com", &arg));
+    assert_eq!(limiter.next_free(), None);
+    // dropped once refilled to full
+    arg.overrides.clear();
+    limiter.take("example.com", &arg);
+    assert_eq!(limiter.hosts.len(), 1);
+    *clock.lock().unwrap() = 10.0;
+    limiter.take("example.org", &arg);
+    assert_eq!(
+        limiter.hosts.keys().collect::<Vec<_>>(),
+        vec!["example.org"]
+    );
+}
@@ -42,14 +42,36 @@
 pub mod actor;
 pub(crate) mod appfut;
 pub mod arg;
+pub mod dupe;
 pub mod engine;
+pub mod event;
+pub mod frontier;
+pub mod handle;
+pub mod host;
+pub mod limiter;
+pub mod pool;
+pub mod stats;
 pub mod vault;
 
 #[doc(inline)]
 pub use actor::Actor;
 #[doc(inline)]
-pub use arg::{ArgAffix, ArgApp, ArgRate};
+pub use arg::{ArgAffix, ArgApp, ArgBudget, ArgDupe, ArgHost, ArgRate, ArgThrottle};
+#[doc(inline)]
+pub use dupe::{DupeFilter, DupeMode};
 #[doc(inline)]
 pub use engine::App;
 #[doc(inline)]
+pub use event::{DropReason, Event};
+#[doc(inline)]
+pub use frontier::{DiskFrontier, Frontier, MemoryFrontier, Queued};
+#[doc(inline)]
+pub use handle::{Handle, Status};
+#[doc(inline)]
+pub use limiter::{Clock, SystemClock, TokenBucket};
+#[doc(inline)]
+pub use pool::{AffixPool, Health, Leave};
+#[doc(inline)]
+pub use stats::{Budget, Latency, Stats, Summary};
+#[doc(inline)]
 pub use vault::{Vault, Vaulted};
@@ -0,0 +1,202 @@
+//! The pool of [Affix] reused by [App] until worn out
+//!
+//! Once the `Request` made of a `Task` and an `Affix` is done, the `Affix` is put back into the
+//! pool instead of thrown away, so an expensive session or token serves many requests. It is
+//! reused up to `arg_affix.reuse` times, and cools down for `nap` seconds before each reuse,
+//! longer while its score is low.
+//!
+//! The score of an `Affix` starts from `1.0`, it is halved on each failed request and recovers
+//! halfway to `1.0` on success. The one failing `arg_affix.max_failures` times in a row, or
+//! banned `arg_affix.max_bans` times, is evicted. A `Response` whose status is listed in
+//! `arg_affix.ban_statuses` is taken as a ban, `429`, `5xx` and requests failed without
+//! `Response` are taken as failures, see [ArgAffix].
+//!
+//! The idle ones are queued in `App.affix`, and the health of those in the pool is kept in
+//! `App.affix_pool`.
+//!
+//! [Affix]: crate::component::Affix
+//! [App]: crate::engine::App
+//! [ArgAffix]: crate::engine::arg::ArgAffix
+use crate::component::Affix;
+use crate::engine::arg::ArgAffix;
+use std::collections::HashMap;
+
+/// the score never falls below it, which bounds the cooldown
+const SCORE_MIN: f64 = 0.01;
+
+/// the health of an `Affix` in the pool
+#[derive(std::fmt::Debug, Clone, Copy, PartialEq)]
+pub struct Health {
+    /// between `0.0` and `1.0`, the higher the healthier
+    pub score: f64,
+    /// number of failures in a row
+    pub failures: u32,
+    /// number of bans
+    pub bans: u32,
+}
+
+impl Health {
+    /// create a healthy one
+    fn new() -> Self {
+        Self {
+            score: 1.0,
+            failures: 0,
+            bans: 0,
+        }
+    }
+
+    fn succeeded(&mut self) {
+        self.score = (self.score + 1.0) / 2.0;
+        self.failures = 0;
+    }
+
+    fn failed(&mut self) {
+        self.score = (self.score / 2.0).max(SCORE_MIN);
+        self.failures += 1;
+    }
+}
+
+impl Default for Health {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// the reason why an `Affix` leaves the pool
+#[derive(std::fmt::Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Leave {
+    /// used `arg_affix.reuse` times
+    Retired,
+    /// failed or banned too many times
+    Evicted,
+}
+
+/// Health of the `Affix` in the pool, keyed by its id
+#[derive(std::fmt::Debug, Default)]
+pub struct AffixPool {
+    /// health of each `Affix` ever observed
+    health: HashMap<u64, Health>,
+    /// number of `Affix` retired
+    pub retired: usize,
+    /// number of `Affix` evicted
+    pub evicted: usize,
+}
+
+impl AffixPool {
+    /// create an instance of [AffixPool]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// get the health of the `Affix` of `id`, `None` if not observed yet
+    pub fn health(&self, id: u64) -> Option<&Health> {
+        self.health.get(&id)
+    }
+
+    /// iterate over the health of each `Affix` observed
+    pub fn iter(&self) -> impl Iterator<Item = (&u64, &Health)> {
+        self.health.iter()
+    }
+
+    /// record the outcome of a `Request` made with the `Affix` of `id`,
+    /// `status` is `None` if it failed without `Response`
+    pub(crate) fn observe(&mut self, id: u64, status: Option<u16>, arg: &ArgAffix) {
+        let health = self.health.entry(id).or_default();
+        match status {
+            Some(status) if arg.ban_statuses.contains(&status) => {
+                health.bans += 1;
+                health.failed();
+            }
+            Some(status) if status != 429 && status < 500 => health.succeeded(),
+            _ => health.failed(),
+        }
+    }
+
+    /// count a use of `affix` done at `now`, then either make it available after the cooldown
+    /// and return `None`, or tell why it leaves the pool
+    pub(crate) fn release(
+        &mut self,
+        affix: &mut Affix,
+        arg: &ArgAffix,
+        nap: f64,
+        now: f64,
+    ) -> Option<Leave> {
+        let info = &mut affix.metap.info;
+        info.used += 1;
+        let health = self.health.get(&info.id).copied().unwrap_or_default();
+        let leave = if (arg.max_failures > 0 && health.failures >= arg.max_failures)
+            || (arg.max_bans > 0 && health.bans >= arg.max_bans)
+        {
+            self.evicted += 1;
+            Leave::Evicted
+        } else if arg.reuse > 0 && info.used >= arg.reuse {
+            self.retired += 1;
+            Leave::Retired
+        } else {
+            // the lower the score, the longer the cooldown
+            info.able = now + nap.max(0.0) / health.score;
+            return None;
+        };
+        self.health.remove(&info.id);
+        Some(leave)
+    }
+}
+
+#[test]
+fn test_pool() {
+    let mut arg = ArgAffix::new();
+    arg.reuse = 3;
+    let mut pool = AffixPool::new();
+    let mut affix = Affix::builder()
+        .body(crate::component::Body::empty())
+        .unwrap();
+    let id = affix.metap.info.id;
+
+    // cools down longer as the score falls
+    pool.observe(id, Some(200), &arg);
+    assert_eq!(pool.health(id).unwrap().score, 1.0);
+    assert_eq!(pool.release(&mut affix, &arg, 2.0, 10.0), None);
+    assert_eq!(affix.metap.info.able, 12.0);
+    pool.observe(id, Some(503), &arg);
+    pool.observe(id, None, &arg);
+    assert_eq!(pool.health(id).unwrap().failures, 2);
+    assert_eq!(pool.release(&mut affix, &arg, 2.0, 20.0), None);
+    assert_eq!(affix.metap.info.able, 28.0);
+    // recovers on success, 404 is not the fault of the affix
+    pool.observe(id, Some(404), &arg);
+    assert_eq!(pool.health(id).unwrap().score, 0.625);
+    assert_eq!(pool.health(id).unwrap().failures, 0);
+    // retired after 3 uses
+    assert_eq!(
+        pool.release(&mut affix, &arg, 0.0, 30.0),
+        Some(Leave::Retired)
+    );
+    assert_eq!(pool.health(id), None);
+    assert_eq!(pool.retired, 1);
+
+    // evicted once banned or failing in a row
+    arg.reuse = 0;
+    let mut banned = Affix::builder()
+        .body(crate::component::Body::empty())
+        .unwrap();
+    banned.metap.info.id = 1;
+    pool.observe(1, Some(403), &arg);
+    assert_eq!(
+        pool.release(&mut banned, &arg, 0.0, 0.0),
+        Some(Leave::Evicted)
+    );
+    let mut failed = Affix::builder()
+        .body(crate::component::Body::empty())
+        .unwrap();
+    failed.metap.info.id = 2;
+    for _ in 0..3 {
+        assert_eq!(pool.release(&mut failed, &arg, 0.0, 0.0), None);
+        pool.observe(2, Some(429), &arg);
+    }
+    assert_eq!(
+        pool.release(&mut failed, &arg, 0.0, 0.0),
+        Some(Leave::Evicted)
+    );
+    assert_eq!(pool.evicted, 2);
+    assert_eq!(pool.iter().count(), 0);
+}
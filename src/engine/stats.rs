@@ -0,0 +1,367 @@
+//! Counters of the data flow in [App] at runtime, and the [Summary] returned by [App::run]
+//!
+//! Besides the overall counters, they are kept for each [Actor] as well, keyed by `Info.marker`.
+//!
+//! [App]: crate::engine::App
+//! [App::run]: crate::engine::App::run
+//! [Actor]: crate::engine::Actor
+use crate::engine::event::DropReason;
+use crate::response::FailureKind;
+use crate::utils;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// maximal number of latency samples kept for percentiles
+const SAMPLES: usize = 10000;
+
+/// Counters of the data flow in [App] at runtime
+///
+/// [App]: crate::engine::App
+#[derive(std::fmt::Debug, Clone)]
+pub struct Stats {
+    /// time stamp at which the `App` starts running
+    pub started: f64,
+    /// number of `Request` spawned
+    pub requests: usize,
+    /// number of `Response` of each status class, eg. `2xx`
+    pub responses: BTreeMap<String, usize>,
+    /// number of failed requests keyed by the name of [FailureKind], eg. `timeout.total`
+    pub failures: BTreeMap<String, usize>,
+    /// number of entities produced by parsers
+    pub entities: usize,
+    /// number of body bytes received
+    pub bytes: usize,
+    /// number of `Task` dropped as duplicate
+    pub dupe_dropped: usize,
+    /// number of `Task` dropped for exceeding `max_depth`
+    pub depth_dropped: usize,
+    /// counters of each `Actor` keyed by its marker
+    pub actors: BTreeMap<String, Stats>,
+    /// the budget whose exhaustion stops the `App`
+    pub budget: Option<Budget>,
+    /// latency samples, uniformly sampled if exceeding `SAMPLES`
+    samples: Vec<f64>,
+    /// number of latency recorded
+    count: usize,
+    /// sum of latency recorded
+    total: f64,
+    /// maximal latency recorded
+    max: f64,
+    /// state of pseudo random number to sample latency
+    seed: u64,
+}
+
+impl Stats {
+    /// create an instance of [Stats]
+    pub fn new() -> Self {
+        Self {
+            started: 0.0,
+            requests: 0,
+            responses: BTreeMap::new(),
+            failures: BTreeMap::new(),
+            entities: 0,
+            bytes: 0,
+            dupe_dropped: 0,
+            depth_dropped: 0,
+            actors: BTreeMap::new(),
+            budget: None,
+            samples: Vec::new(),
+            count: 0,
+            total: 0.0,
+            max: 0.0,
+            seed: 0x2545f4914f6cdd1d,
+        }
+    }
+
+    /// get the counters of the `Actor` whose marker is `marker`
+    pub fn actor(&self, marker: &str) -> Option<&Stats> {
+        self.actors.get(marker)
+    }
+
+    /// apply `f` to the overall counters and those of `marker` if not empty
+    fn record<F>(&mut self, marker: &str, f: F)
+    where
+        F: Fn(&mut Stats),
+    {
+        f(self);
+        if !marker.is_empty() {
+            let started = self.started;
+            let stats = self.actors.entry(marker.to_string()).or_insert_with(|| {
+                let mut stats = Stats::new();
+                stats.started = started;
+                stats
+            });
+            f(stats);
+        }
+    }
+
+    /// number of failed requests and `Response` of status `4xx` or `5xx`
+    pub fn errors(&self) -> usize {
+        let failed = self.failures.values().sum::<usize>();
+        let status = ["4xx", "5xx"]
+            .iter()
+            .filter_map(|class| self.responses.get(*class))
+            .sum::<usize>();
+        failed + status
+    }
+
+    /// record a spawned `Request`
+    pub(crate) fn request(&mut self, marker: &str) {
+        self.record(marker, |stats| stats.requests += 1);
+    }
+
+    /// record a received `Response`
+    pub(crate) fn response(&mut self, marker: &str, status: u16, bytes: usize, latency: f64) {
+        self.record(marker, |stats| {
+            *stats
+                .responses
+                .entry(format!("{}xx", status / 100))
+                .or_insert(0) += 1;
+            stats.bytes += bytes;
+            stats.latency(latency);
+        });
+    }
+
+    /// record failed requests of `kind`
+    pub(crate) fn failure(&mut self, marker: &str, kind: FailureKind, n: usize) {
+        if n > 0 {
+            self.record(marker, |stats| {
+                *stats.failures.entry(kind.as_str().to_string()).or_insert(0) += n;
+            });
+        }
+    }
+
+    /// record entities produced
+    pub(crate) fn entities(&mut self, marker: &str, n: usize) {
+        self.record(marker, |stats| stats.entities += n);
+    }
+
+    /// record dropped `Task`
+    pub(crate) fn dropped(&mut self, marker: &str, reason: DropReason, n: usize) {
+        self.record(marker, |stats| match reason {
+            DropReason::Duplicate => stats.dupe_dropped += n,
+            DropReason::Depth => stats.depth_dropped += n,
+        });
+    }
+
+    /// record the duration to receive a `Response`
+    pub(crate) fn latency(&mut self, latency: f64) {
+        self.count += 1;
+        self.total += latency;
+        self.max = self.max.max(latency);
+        if self.samples.len() < SAMPLES {
+            self.samples.push(latency);
+            return;
+        }
+        // reservoir sampling
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 7;
+        self.seed ^= self.seed << 17;
+        let index = (self.seed % self.count as u64) as usize;
+        if index < SAMPLES {
+            self.samples[index] = latency;
+        }
+    }
+
+    /// get the summary of latency
+    pub fn latency_summary(&self) -> Latency {
+        if self.count == 0 {
+            return Latency::default();
+        }
+        let mut samples = self.samples.clone();
+        samples.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        let percentile = |p: f64| {
+            let rank = (p * samples.len() as f64).ceil() as usize;
+            samples[rank.clamp(1, samples.len()) - 1]
+        };
+        Latency {
+            mean: self.total / self.count as f64,
+            p50: percentile(0.5),
+            p90: percentile(0.9),
+            p99: percentile(0.99),
+            max: self.max,
+        }
+    }
+
+    /// get the summary of the data flow so far
+    pub fn summary(&self) -> Summary {
+        Summary {
+            started: self.started,
+            finished: utils::now(),
+            requests: self.requests,
+            responses: self.responses.clone(),
+            failures: self.failures.clone(),
+            entities: self.entities,
+            dupe_dropped: self.dupe_dropped,
+            depth_dropped: self.depth_dropped,
+            bytes: self.bytes,
+            latency: self.latency_summary(),
+            budget: self.budget,
+            actors: self
+                .actors
+                .iter()
+                .map(|(marker, stats)| (marker.clone(), stats.summary()))
+                .collect(),
+        }
+    }
+}
+
+impl Default for Stats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// a budget of [ArgBudget], whose exhaustion stops the [App] gracefully
+///
+/// [App]: crate::engine::App
+/// [ArgBudget]: crate::engine::arg::ArgBudget
+#[derive(std::fmt::Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Budget {
+    /// number of `Request` spawned
+    Requests,
+    /// number of `Response` received
+    Responses,
+    /// number of entities produced
+    Entities,
+    /// number of failed requests and `Response` of status `4xx` or `5xx`
+    Errors,
+    /// duration in seconds since the `App` starts running
+    Wall,
+    /// number of body bytes received
+    Bytes,
+}
+
+impl Budget {
+    /// the name of the budget, the same as the key of `budget.*` in config
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Budget::Requests => "requests",
+            Budget::Responses => "responses",
+            Budget::Entities => "entities",
+            Budget::Errors => "errors",
+            Budget::Wall => "wall",
+            Budget::Bytes => "bytes",
+        }
+    }
+}
+
+impl std::fmt::Display for Budget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Latency in seconds to receive the `Response`
+#[derive(std::fmt::Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Latency {
+    /// the average
+    pub mean: f64,
+    /// the median
+    pub p50: f64,
+    /// the 90th percentile
+    pub p90: f64,
+    /// the 99th percentile
+    pub p99: f64,
+    /// the maximum
+    pub max: f64,
+}
+
+/// Summary of a run of [App], returned by [App::run]
+///
+/// [App]: crate::engine::App
+/// [App::run]: crate::engine::App::run
+#[derive(std::fmt::Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Summary {
+    /// time stamp at which the `App` starts running
+    pub started: f64,
+    /// time stamp at which the `App` finishes running
+    pub finished: f64,
+    /// number of `Request` spawned
+    pub requests: usize,
+    /// number of `Response` of each status class, eg. `2xx`
+    pub responses: BTreeMap<String, usize>,
+    /// number of failed requests keyed by the name of [FailureKind]
+    pub failures: BTreeMap<String, usize>,
+    /// number of entities produced
+    pub entities: usize,
+    /// number of `Task` dropped as duplicate
+    pub dupe_dropped: usize,
+    /// number of `Task` dropped for exceeding `max_depth`
+    pub depth_dropped: usize,
+    /// number of body bytes received
+    pub bytes: usize,
+    /// latency to receive the `Response`
+    pub latency: Latency,
+    /// the budget whose exhaustion stops the `App`, `None` if it finishes all the work
+    /// or is stopped otherwise
+    #[serde(default)]
+    pub budget: Option<Budget>,
+    /// summary of each `Actor` keyed by its marker
+    #[serde(default)]
+    pub actors: BTreeMap<String, Summary>,
+}
+
+impl Summary {
+    /// number of `Response` whose status is `2xx`
+    pub fn succeeded(&self) -> usize {
+        self.responses.get("2xx").copied().unwrap_or(0)
+    }
+
+    /// number of `Task` dropped
+    pub fn dropped(&self) -> usize {
+        self.dupe_dropped + self.depth_dropped
+    }
+
+    /// number of failed requests of `kind`
+    pub fn failed(&self, kind: FailureKind) -> usize {
+        self.failures.get(kind.as_str()).copied().unwrap_or(0)
+    }
+}
+
+#[test]
+fn test_stats() {
+    let mut stats = Stats::new();
+    assert_eq!(stats.latency_summary().max, 0.0);
+    for i in 1..=100 {
+        let marker = if i % 2 == 0 { "even" } else { "" };
+        stats.response(marker, if i % 10 == 0 { 404 } else { 200 }, 10, i as f64);
+    }
+    stats.failure("even", FailureKind::Dns, 2);
+    stats.failure("", FailureKind::Dns, 0);
+    stats.dropped("odd", DropReason::Depth, 3);
+    assert_eq!(stats.responses["2xx"], 90);
+    assert_eq!(stats.responses["4xx"], 10);
+    assert_eq!(stats.failures["dns"], 2);
+    assert_eq!(stats.bytes, 1000);
+    assert_eq!(stats.errors(), 12);
+    let latency = stats.latency_summary();
+    assert_eq!(latency.mean, 50.5);
+    assert_eq!(latency.p50, 50.0);
+    assert_eq!(latency.p90, 90.0);
+    assert_eq!(latency.p99, 99.0);
+    assert_eq!(latency.max, 100.0);
+
+    // counted for each actor as well
+    let even = stats.actor("even").unwrap();
+    assert_eq!(even.responses["2xx"], 40);
+    assert_eq!(even.failures["dns"], 2);
+    assert_eq!(even.latency_summary().max, 100.0);
+    let summary = stats.summary();
+    assert_eq!(summary.depth_dropped, 3);
+    assert_eq!(summary.actors["odd"].dropped(), 3);
+    assert_eq!(summary.actors.len(), 2);
+    assert_eq!(summary.failed(FailureKind::Dns), 2);
+    assert_eq!(summary.failed(FailureKind::Tls), 0);
+
+    // sampled but mean kept exact
+    for _ in 0..SAMPLES * 2 {
+        stats.latency(1.0);
+    }
+    assert_eq!(stats.samples.len(), SAMPLES);
+    assert_eq!(stats.latency_summary().p50, 1.0);
+    // the maximum kept exact once evicted from the samples
+    assert!(!stats.samples.contains(&100.0));
+    assert_eq!(stats.latency_summary().max, 100.0);
+}
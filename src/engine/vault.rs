@@ -1,70 +1,129 @@
-//! A Lock-Free and Thread-Safe reference-counting pointer data container.
+//! A Thread-Safe reference-counting data container shared by [App] and the tasks it spawns.
 //!
-//! Similar to [Arc]<[Mutex]>,
-//! it not only provides shared owership of a value,
-//! protects the data from mutation, but also **NOT BLOCKING** the thread and can send between
-//! thread safely.
+//! Similar to [Arc]<[Mutex]>, it provides shared owership of a value and protects the data
+//! from mutation racing. [Clone::clone] return an `Vault<U>` instance which points to the same
+//! heap-allocated data.
 //!
-//! [Clone::clone] return an `Vault<U>` instance which points to the heap-allocated source data
-//! with reference counter increaced by 1.
-//! when borrowed as mutable, it is sealed as [Vaulted],
-//! only one mutable reference are allowed.
+//! The data is only accessible through its guard [Vaulted], one at a time. When contended,
+//! [Vault::as_mut] and [Vault::as_ref] park the thread instead of spinning, [Vault::lock] can
+//! be awaited, and [Vault::try_lock] returns `None` instead.
+//!
+//! **Note that** the guard is dropped as soon as possible, never held across `.await` or while
+//! accessing the same `Vault` again.
 //!
 //! it only get dropped when all clones dropped
 //!
+//! It is checked under [loom] by running
+//! `RUSTFLAGS="--cfg dyer_loom" cargo test --lib --release test_loom`, including the
+//! wake-up of the awaiting [Vault::lock]
+//!
+//! [loom]: https://docs.rs/loom
+//! [App]: crate::engine::App
 //! [Arc]: std::sync::Arc
 //! [Mutex]: std::sync::Mutex
 //!
-use std::ptr::NonNull;
-use std::sync::atomic::{self, AtomicBool, AtomicUsize, Ordering};
+#[cfg(dyer_loom)]
+use loom::sync::{Arc, Mutex, MutexGuard};
+use std::mem::ManuallyDrop;
+#[cfg(not(dyer_loom))]
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::sync::{PoisonError, TryLockError};
+use tokio::sync::Notify;
 
+#[cfg(not(dyer_loom))]
 #[test]
 fn test_unit() {
-    let mut u = Vault::new(0);
+    let u = Vault::new(0);
     {
         *(u.as_mut()) = 1;
-        assert_eq!(u.as_ref(), &1);
-        assert_eq!(u.sealed.get_mut(), &false);
+        assert_eq!(*u.as_ref(), 1);
         let mut up = u.as_mut();
         *up += 1;
         assert_eq!(*up, 2);
+        assert!(u.try_lock().is_none());
     }
-    assert_eq!(*u, 2);
-    assert_eq!(u.sealed.get_mut(), &false);
-}
+    assert_eq!(*u.as_ref(), 2);
+    assert_eq!(u.replace(3), 2);
+    assert_eq!(u.take(), 3);
 
-/// A Lock-Free and Thread-Safe reference-counting data container
-#[derive(std::fmt::Debug)]
-pub struct Vault<U> {
-    sealed: AtomicBool,
-    inner: NonNull<Inner<U>>,
+    // awaited until the other thread releases it
+    let other = u.clone();
+    let guard = std::sync::Arc::new(std::sync::Barrier::new(2));
+    let barrier = guard.clone();
+    let th = std::thread::spawn(move || {
+        let mut up = other.as_mut();
+        barrier.wait();
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        *up = 4;
+    });
+    guard.wait();
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    assert_eq!(rt.block_on(async { *u.lock().await }), 4);
+    th.join().unwrap();
 }
 
-unsafe impl<U> Send for Vault<U> {}
-
-struct Inner<U> {
-    cn: AtomicUsize,
-    data: U,
+#[cfg(dyer_loom)]
+#[test]
+fn test_loom() {
+    loom::model(|| {
+        let vault = Vault::new(Vec::new());
+        let handles = (0..2)
+            .map(|i| {
+                let vault = vault.clone();
+                loom::thread::spawn(move || vault.as_mut().push(i))
+            })
+            .collect::<Vec<_>>();
+        let len = vault.as_ref().len();
+        assert!(len <= 2);
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        let mut items = vault.take();
+        items.sort_unstable();
+        assert_eq!(items, vec![0, 1]);
+    });
 }
 
-impl<U> Clone for Vault<U> {
-    fn clone(&self) -> Self {
-        self.inner().cn.fetch_add(1, Ordering::Relaxed);
-        Self::from_inner(self.inner)
-    }
+#[cfg(dyer_loom)]
+#[test]
+fn test_loom_lock() {
+    // the awaiting `lock` always makes progress once the holder releases it,
+    // a lost wake-up shows up as a deadlock
+    loom::model(|| {
+        let vault = Vault::new(0);
+        let mut held = vault.as_mut();
+        let contender = {
+            let vault = vault.clone();
+            loom::thread::spawn(move || {
+                let mut guard = loom::future::block_on(vault.lock());
+                *guard += 1;
+            })
+        };
+        // let the contender run into `lock` while it is held
+        loom::thread::yield_now();
+        *held += 1;
+        drop(held);
+        contender.join().unwrap();
+        assert_eq!(*vault.as_ref(), 2);
+    });
 }
 
-impl<U> std::ops::Deref for Vault<U> {
-    type Target = U;
+/// A Thread-Safe reference-counting data container
+pub struct Vault<U> {
+    inner: Arc<Inner<U>>,
+}
 
-    fn deref(&self) -> &Self::Target {
-        &self.inner().data
-    }
+struct Inner<U> {
+    data: Mutex<U>,
+    /// notify the waiting `lock` once unlocked
+    unlocked: Notify,
 }
 
-impl<U> std::ops::DerefMut for Vault<U> {
-    fn deref_mut(&mut self) -> &mut U {
-        unsafe { &mut self.inner.as_mut().data }
+impl<U> Clone for Vault<U> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
     }
 }
 
@@ -74,120 +133,103 @@ impl<U: Default> Default for Vault<U> {
     }
 }
 
-impl<U> Drop for Vault<U> {
-    fn drop(&mut self) {
-        if self.inner().cn.fetch_sub(1, Ordering::Release) != 1 {
-            //println!("droping vault: {:?}, Not Dropped", self.inner().cn);
-            return;
+impl<U: std::fmt::Debug> std::fmt::Debug for Vault<U> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.try_lock() {
+            Some(data) => f.debug_struct("Vault").field("data", &*data).finish(),
+            None => f.debug_struct("Vault").field("data", &"<locked>").finish(),
         }
-        //println!("droping vault: {:?}, Dropped", self.inner().cn);
-        atomic::fence(Ordering::Acquire);
-        //self.inner().cn.load(Ordering::Acquire);
-        self.drop_slow()
     }
 }
 
 impl<U> Vault<U> {
     pub fn new(d: U) -> Self {
-        let dm: Box<_> = Box::new(Inner {
-            cn: AtomicUsize::new(1),
-            data: d,
-        });
-        Self::from_inner(Box::leak(dm).into())
+        Self {
+            inner: Arc::new(Inner {
+                data: Mutex::new(d),
+                unlocked: Notify::new(),
+            }),
+        }
     }
 
-    pub fn take(&mut self) -> U
+    pub fn take(&self) -> U
     where
         U: Default,
     {
         self.replace(U::default())
     }
 
-    pub fn replace(&mut self, src: U) -> U {
-        std::mem::replace(&mut **self, src)
+    pub fn replace(&self, src: U) -> U {
+        std::mem::replace(&mut *self.as_mut(), src)
     }
 
-    #[allow(dead_code)]
-    pub fn swap(&mut self, dst: &mut U) {
-        std::mem::swap(&mut **self, dst);
+    pub fn swap(&self, dst: &mut U) {
+        std::mem::swap(&mut *self.as_mut(), dst);
     }
 
-    #[allow(dead_code)]
-    pub fn map<R, F>(&mut self, mut f: F) -> R
+    pub fn map<R, F>(&self, mut f: F) -> R
     where
         F: FnMut(&mut U) -> R,
     {
-        unsafe { f(&mut self.inner.as_mut().data) }
+        f(&mut *self.as_mut())
     }
 
-    #[allow(dead_code)]
-    fn from_ptr(ptr: *mut Inner<U>) -> Self {
-        unsafe { Self::from_inner(NonNull::new_unchecked(ptr)) }
+    /// get the guard to mutate the data, the thread is parked until it is available
+    pub fn as_mut(&self) -> Vaulted<'_, U> {
+        let guard = self
+            .inner
+            .data
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        Vaulted::new(guard, &self.inner.unlocked)
     }
 
-    fn inner(&self) -> &Inner<U> {
-        unsafe { self.inner.as_ref() }
+    /// get the guard to read the data, the thread is parked until it is available
+    pub fn as_ref(&self) -> Vaulted<'_, U> {
+        self.as_mut()
     }
 
-    pub fn as_mut(&mut self) -> Vaulted<U> {
-        while self.sealed.load(Ordering::Acquire) {}
-        self.sealed.store(true, Ordering::Release);
-        Vaulted(self)
+    /// get the guard if it is available for now
+    pub fn try_lock(&self) -> Option<Vaulted<'_, U>> {
+        let guard = match self.inner.data.try_lock() {
+            Ok(guard) => guard,
+            Err(TryLockError::Poisoned(e)) => e.into_inner(),
+            Err(TryLockError::WouldBlock) => return None,
+        };
+        Some(Vaulted::new(guard, &self.inner.unlocked))
     }
 
-    #[allow(dead_code)]
-    pub fn as_ref(&self) -> &U {
-        while self.sealed.load(Ordering::Relaxed) {}
-        unsafe { &self.inner.as_ref().data }
+    /// get the guard, wait asynchronously until it is available
+    pub async fn lock(&self) -> Vaulted<'_, U> {
+        loop {
+            if let Some(guard) = self.try_lock() {
+                return guard;
+            }
+            self.inner.unlocked.notified().await;
+        }
     }
 
-    #[allow(dead_code)]
-    pub(crate) fn update<F, R>(&mut self, mut f: F) -> R
+    pub(crate) fn update<F, R>(&self, f: F) -> R
     where
         F: FnMut(&mut U) -> R,
     {
-        while self.sealed.load(Ordering::Acquire) {}
-        f(&mut *self)
-    }
-
-    #[allow(dead_code)]
-    pub(crate) fn try_unseal(&mut self) {
-        if self.sealed.load(Ordering::Acquire) {
-            self.sealed.store(false, Ordering::Release);
-        }
-    }
-
-    #[allow(dead_code)]
-    pub(crate) fn unseal(&mut self) {
-        while self.sealed.load(Ordering::Acquire) {
-            self.sealed.store(false, Ordering::Release);
-        }
-    }
-
-    unsafe fn get_mut_unchecked(this: &mut Self) -> &mut U {
-        &mut (*this.inner.as_ptr()).data
-    }
-
-    fn drop_slow(&mut self) {
-        unsafe { std::ptr::drop_in_place(Self::get_mut_unchecked(self)) };
-    }
-
-    fn from_inner(ptr: NonNull<Inner<U>>) -> Self {
-        Self {
-            sealed: AtomicBool::new(false),
-            inner: ptr,
-        }
+        self.map(f)
     }
 }
 
 /// Serve as a `scoped lock` of [Vault], when it is dropped(falls out of scope), [Vault] will be
 /// Available
-pub struct Vaulted<'a, U>(&'a mut Vault<U>);
+pub struct Vaulted<'a, U> {
+    guard: ManuallyDrop<MutexGuard<'a, U>>,
+    unlocked: &'a Notify,
+}
 
 impl<'a, U> Vaulted<'a, U> {
-    #[allow(dead_code)]
-    fn new(d: &'a mut Vault<U>) -> Self {
-        Vaulted(d)
+    fn new(guard: MutexGuard<'a, U>, unlocked: &'a Notify) -> Self {
+        Vaulted {
+            guard: ManuallyDrop::new(guard),
+            unlocked,
+        }
     }
 }
 
@@ -195,19 +237,20 @@ impl<U> std::ops::Deref for Vaulted<'_, U> {
     type Target = U;
 
     fn deref(&self) -> &Self::Target {
-        &self.0.inner().data
+        &self.guard
     }
 }
 
 impl<U> std::ops::DerefMut for Vaulted<'_, U> {
     fn deref_mut(&mut self) -> &mut U {
-        unsafe { &mut self.0.inner.as_mut().data }
+        &mut self.guard
     }
 }
 
 impl<U> Drop for Vaulted<'_, U> {
     fn drop(&mut self) {
-        while !self.0.sealed.load(Ordering::Acquire) {}
-        self.0.sealed.store(false, Ordering::Release);
+        // unlock before notifying, or the woken one finds it still locked
+        unsafe { ManuallyDrop::drop(&mut self.guard) };
+        self.unlocked.notify_one();
     }
 }
@@ -50,14 +50,21 @@ pub use component::xpath_stable;
 #[doc(inline)]
 pub use component::ConcatText;
 #[doc(inline)]
-pub use component::{affix, body, client, couple, info, parsed, request, response, task, utils};
+pub use component::{
+    affinity, affix, body, client, couple, info, parsed, request, response, retry, schedule, task,
+    utils,
+};
 #[doc(inline)]
 pub use component::{
     Affix, Body, Buf, Bytes, Client, ClientType, Couple, Info, MetaRequest, MetaResponse, MetaTask,
-    Parsed, Request, Response, Task,
+    Parsed, Request, Response, RetryPolicy, Schedule, Task,
 };
 #[doc(inline)]
-pub use engine::{Actor, App, ArgAffix, ArgApp, ArgRate};
+pub use engine::{
+    Actor, AffixPool, App, ArgAffix, ArgApp, ArgBudget, ArgDupe, ArgHost, ArgRate, ArgThrottle,
+    Budget, DiskFrontier, DropReason, DupeFilter, DupeMode, Event, Frontier, Handle,
+    MemoryFrontier, Stats, Status, Summary,
+};
 #[doc(inline)]
 pub use http::Extensions;
 #[doc(inline)]
@@ -174,6 +174,31 @@ pub mod serde_uri {
     }
 }
 
+/// mod that contains serialize funtion and deserialize funtion to ser-de Option variant of [http::Uri]
+///
+pub mod serde_uri_op {
+    use super::*;
+    pub fn serialize<S>(arg: &Option<Uri>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match arg {
+            Some(uri) => serializer.serialize_some(&uri.to_string()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Uri>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match Option::<String>::deserialize(deserializer)? {
+            Some(raw) => Ok(Some(Uri::from_str(&raw).unwrap())),
+            None => Ok(None),
+        }
+    }
+}
+
 /// mod that contains serialize funtion and deserialize funtion to ser-de [http::header::HeaderName]
 ///
 pub mod serde_headername {
@@ -29,10 +29,11 @@
 //! assert_eq!(middleware.rank(), 0);
 //! ```
 
-use crate::component::{Affix, Request, Response, Task};
+use crate::component::{retry, Affix, Info, Request, Response, Task};
 use crate::engine::App;
 use crate::plugin::BoxFuture;
-use crate::response::MetaResponse;
+use crate::response::{FailureKind, MetaResponse};
+use crate::utils;
 use http::Extensions;
 
 /// default method for process `Affix` in `MiddleWare`
@@ -51,81 +52,116 @@ pub async fn hres<E>(_res: &mut Vec<Response>, _app: &mut App<E>) {}
 pub async fn hitem<E>(_items: &mut Vec<E>, _app: &mut App<E>) {}
 
 /// default method for process failed `Response` in `MiddleWare`
+///
+/// the failed one is retried according to its [RetryPolicy], the others are dropped into
+/// `App.errs`, with the recurring `Task` put back to run again and the `Affix` put back into
+/// the pool
+///
+/// [RetryPolicy]: crate::component::retry::RetryPolicy
 pub async fn herr<E>(_res: &mut Vec<Result<Response, MetaResponse>>, _app: &mut App<E>) {
-    let tasks = Vec::new();
-    let affixs = Vec::new();
     let mut reqs = Vec::new();
     let mut yerrs = Vec::new();
+    let now = utils::now();
     while let Some(res) = _res.pop() {
-        match res {
-            Ok(mut item) => {
+        let req = match res {
+            Ok(ref item) => {
+                let status = item.status().as_u16();
                 log::error!(
                     "Response Failed: {}, uri: {}",
-                    item.status().as_u16(),
+                    status,
                     item.metas.info.from.to_string()
                 );
-                let used = item.metas.info.used;
-                if used > 2 {
-                    /*
-                     *let errs = format!(
-                     *    "status: {}\turi: {}\tcontent: {:?}\n",
-                     *    &item.status().as_str(),
-                     *    &item.metas.info.from.to_string(),
-                     *    item.body
-                     *);
-                     */
-                    log::error!("Task Fails 3+ times. drop it.");
-                    // remove affix  as default
-                    yerrs.push(Ok(item));
+                let info = &item.metas.info;
+                let policy = info.retry.as_ref().unwrap_or(&_app.args.retry);
+                let retry_after = retry::retry_after(item.headers(), now);
+                if policy.retry_status(status) {
+                    retried(_app, info, retry_after, now)
                 } else {
-                    item.metas.info.used += 1;
-                    log::error!("{} Times Failure, Reuse This Task.", used + 1);
-                    if let Some(couple) = _app.couple.get(&item.metas.info.id) {
-                        let req = Request::from_couple(
-                            couple,
-                            None,
-                            _app.exts_t_fn.as_ref(),
-                            _app.exts_p_fn.as_ref(),
-                        );
-                        reqs.push(req);
-                    }
+                    None
                 }
             }
-            Err(mut m) => {
-                log::error!("Response Failed uri: {}", m.info.from.to_string());
-                let used = m.info.used;
-                if used > 2 {
-                    /*
-                     *let errs = format!(
-                     *    "cannot make a request to uri: {}\n",
-                     *    &m.info.from.to_string(),
-                     *);
-                     */
-                    log::error!("Task Fails 3+ times. drop it.");
-                    // remove affix  as default
-                    yerrs.push(Err(m));
+            Err(ref m) => {
+                let kind = m.failure.unwrap_or(FailureKind::Other);
+                log::error!(
+                    "Response Failed: {}, uri: {}",
+                    kind,
+                    m.info.from.to_string()
+                );
+                let policy = m.info.retry.as_ref().unwrap_or(&_app.args.retry);
+                if policy.retry_failure(kind) {
+                    retried(_app, &m.info, None, now)
                 } else {
-                    m.info.used += 1;
-                    log::error!("{} Times Failure, Reuse This Task.", used + 1);
-                    if let Some(couple) = _app.couple.get(&m.info.id) {
-                        let req = Request::from_couple(
-                            couple,
-                            None,
-                            _app.exts_t_fn.as_ref(),
-                            _app.exts_p_fn.as_ref(),
-                        );
-                        reqs.push(req);
-                    }
+                    None
                 }
             }
+        };
+        match req {
+            Some(req) => reqs.push(req),
+            None => {
+                let info = match res {
+                    Ok(ref item) => &item.metas.info,
+                    Err(ref m) => &m.info,
+                };
+                finish(_app, info, now);
+                yerrs.push(res);
+            }
         }
     }
-    _app.task.as_mut().extend(tasks);
-    _app.affix.as_mut().extend(affixs);
     _app.req.as_mut().extend(reqs);
     _app.errs.as_mut().extend(yerrs);
 }
 
+/// rebuild the `Request` from its couple to retry if attempts left,
+/// available after the delay of its [RetryPolicy]
+///
+/// [RetryPolicy]: crate::component::retry::RetryPolicy
+fn retried<E>(app: &App<E>, info: &Info, retry_after: Option<f64>, now: f64) -> Option<Request> {
+    let policy = info.retry.as_ref().unwrap_or(&app.args.retry);
+    if policy.exhausted(info.used) {
+        log::error!("Task Fails {} times. drop it.", info.used + 1);
+        return None;
+    }
+    let mut couples = app.couple.as_mut();
+    let couple = couples.get_mut(&info.id)?;
+    // attempts are counted on the couple, from which the `Request` is rebuilt
+    let task = &mut couple.task.metat.info;
+    task.used = info.used + 1;
+    let rand = (utils::now() * 3000.0) % 1.0;
+    let delay = policy.delay(task.used, retry_after, rand);
+    task.able = now + delay;
+    log::error!(
+        "{} Times Failure, Retry This Task in {:.1} seconds.",
+        task.used,
+        delay
+    );
+    Some(Request::from_couple(
+        couple,
+        None,
+        app.exts_t_fn.as_ref(),
+        app.exts_p_fn.as_ref(),
+    ))
+}
+
+/// put the recurring `Task` of the failed one back to run again by its schedule and its `Affix`
+/// back into the pool, the couple is dropped then, otherwise kept as it was
+fn finish<E>(app: &mut App<E>, info: &Info, now: f64) {
+    let reused = match app.couple.as_ref().get(&info.id) {
+        Some(couple) => couple.affix.is_some() || info.schedule.is_some(),
+        None => false,
+    };
+    if !reused {
+        return;
+    }
+    let couple = app.couple.as_mut().remove(&info.id).unwrap();
+    if let Some(affix) = couple.affix {
+        app.release_affix(affix);
+    }
+    if let Some(task) = couple.task.recur(now) {
+        log::info!("Task {} runs again at {}", info.id, task.metat.info.able);
+        app.task.as_mut().push(task);
+    }
+}
+
 /// default method for failing parsing `Response` in `MiddleWare`
 pub async fn hyerr<E>(_res: &mut Vec<Result<Response, MetaResponse>>, _app: &mut App<E>) {}
 
@@ -532,3 +568,57 @@ impl<'md, E> MiddleWareBuilder<'md, E> {
     builder!(err, err, handle_err, Result<Response, MetaResponse>, err_ref, MiddleWareBuilder::new());
     builder!(yerr, yerr, handle_yerr, Result<Response, MetaResponse>, yerr_ref, MiddleWareBuilder::new());
 }
+
+#[tokio::test]
+async fn test_herr() {
+    use crate::component::{Body, Couple, Parsed};
+    use crate::response::TimeoutKind;
+    fn parse(_: Response) -> Parsed<()> {
+        todo!()
+    }
+
+    let mut app = App::<()>::new();
+    let task = Task::get("https://example.com/")
+        .parser(parse)
+        .body(Body::empty(), "marker")
+        .unwrap();
+    let couple = Couple::new(task, None);
+    let id = couple.id;
+    let failed = |app: &App<()>, kind: FailureKind| {
+        let couples = app.couple.as_ref();
+        let req = Request::from_couple(couples.get(&id).unwrap(), None, None, None);
+        let mut meta = MetaResponse::from(req.metar);
+        meta.failure = Some(kind);
+        vec![Err(meta)]
+    };
+    app.couple.as_mut().insert(id, couple);
+
+    // retried with backoff until attempts exhausted
+    let now = utils::now();
+    for used in 1..=2 {
+        let mut res = failed(&app, FailureKind::Timeout(TimeoutKind::Total));
+        herr(&mut res, &mut app).await;
+        let req = app.req.as_mut().pop(f64::MAX).unwrap();
+        assert_eq!(req.metar.info.used, used);
+        assert!(req.metar.info.able > now);
+        assert!(app.errs.as_ref().is_empty());
+    }
+    let mut res = failed(&app, FailureKind::Timeout(TimeoutKind::Total));
+    herr(&mut res, &mut app).await;
+    assert!(app.req.as_ref().is_empty());
+    assert_eq!(app.errs.as_ref().len(), 1);
+
+    // not retried for the kind
+    app.couple
+        .as_mut()
+        .get_mut(&id)
+        .unwrap()
+        .task
+        .metat
+        .info
+        .used = 0;
+    let mut res = failed(&app, FailureKind::TooLarge);
+    herr(&mut res, &mut app).await;
+    assert!(app.req.as_ref().is_empty());
+    assert_eq!(app.errs.as_ref().len(), 2);
+}